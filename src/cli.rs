@@ -0,0 +1,529 @@
+use crate::input::Source;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: advent2023 [run] [DAYS] [--part N] [--example[=K] | --input PATH | -]
+       advent2023 bench [DAYS] [--part N] [--runs N] [--example[=K] | --input PATH | -]
+       advent2023 dot [--paths] [--start REGEX] [--end REGEX] [--alphabet CHARS]
+                      [--example[=K] | --input PATH | -]
+       advent2023 hands [--poker] [--wild RANKS] [--example[=K] | --input PATH | -]
+
+DAYS is a single day (7), a range (3..9 or 3..=9), a comma-separated
+list of those (1,3..=5) or `all`. Defaults to all days.
+
+Inputs are read from `NN.txt` in the input directory, which defaults to
+`input` and can be changed with the AOC_INPUT_DIR environment variable.
+
+Options:
+  -p, --part N      Only run part N (1 or 2)
+  -e, --example     Read the example input `NN_test.txt` instead
+      --example=K   Read the Kth example input `NN_testK.txt` instead
+  -i, --input PATH  Read the input from PATH, or from stdin if PATH is `-`
+  -                 Read the input from stdin
+      --runs N      Number of times `bench` runs each part (default 10)
+      --paths       Draw the path of every ghost in the day 8 network
+      --start REGEX Nodes the ghosts start on (default: names ending in A)
+      --end REGEX   Nodes the ghosts end on (default: names ending in Z)
+      --alphabet CHARS
+                    Instruction characters, one per child of each node
+                    (default: LR)
+      --poker       Play the day 7 hands as poker, written with suits
+      --wild RANKS  Play the day 7 cards of these ranks, such as `J`, as jokers
+
+`dot` writes the day 8 network as a Graphviz graph to stdout, which can be
+rendered with `dot -Tsvg`.
+
+`hands` prints the total winnings of the day 7 hands played by other rules,
+Camel Cards unless `--poker` is given.";
+
+const DEFAULT_RUNS: usize = 10;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run(Selection),
+    Bench(Selection, usize),
+    Dot(Dot),
+    Hands(Hands),
+    Help,
+}
+
+/// How to export the day 8 network.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Dot {
+    pub input: Source,
+    /// Whether to draw the path of every ghost
+    pub paths: bool,
+    /// Regular expressions for the names of the start and end nodes
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// The instruction characters, in the order of each node's children
+    pub alphabet: Option<String>,
+}
+
+/// How to play the day 7 hands.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hands {
+    pub input: Source,
+    /// Whether to play poker rather than Camel Cards
+    pub poker: bool,
+    /// The ranks played as wildcards, if not the default of the rules
+    pub wild: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Selection {
+    pub days: Vec<u8>,
+    pub part: Option<u8>,
+    pub input: Source,
+}
+
+impl Selection {
+    pub fn all() -> Self {
+        Selection {
+            days: (1..=25).collect(),
+            part: None,
+            input: Source::Puzzle,
+        }
+    }
+
+    pub fn includes(&self, day: u8, part: u8) -> bool {
+        self.days.contains(&day) && self.part.is_none_or(|p| p == part)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn parse_day(input: &str) -> Result<u8, CliError> {
+    match input.parse::<u8>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(CliError(format!("Invalid day: {}", input))),
+    }
+}
+
+fn parse_range(input: &str) -> Result<RangeInclusive<u8>, CliError> {
+    if let Some((start, end)) = input.split_once("..=") {
+        Ok(parse_day(start)?..=parse_day(end)?)
+    } else if let Some((start, end)) = input.split_once("..") {
+        let end = parse_day(end)?;
+        if end == 1 {
+            return Err(CliError(format!("Empty range: {}", input)));
+        }
+        Ok(parse_day(start)?..=end - 1)
+    } else {
+        let day = parse_day(input)?;
+        Ok(day..=day)
+    }
+}
+
+pub fn parse_days(input: &str) -> Result<Vec<u8>, CliError> {
+    if input == "all" {
+        return Ok(Selection::all().days);
+    }
+
+    let mut days = vec![];
+    for part in input.split(',') {
+        let range = parse_range(part)?;
+        if range.is_empty() {
+            return Err(CliError(format!("Empty range: {}", part)));
+        }
+        days.extend(range);
+    }
+    days.sort();
+    days.dedup();
+    Ok(days)
+}
+
+fn parse_example(input: &str) -> Result<Source, CliError> {
+    match input.parse::<u8>() {
+        Ok(n) if n > 0 => Ok(Source::Example(Some(n))),
+        _ => Err(CliError(format!("Invalid example: {}", input))),
+    }
+}
+
+fn parse_input(input: &str) -> Source {
+    if input == "-" {
+        Source::Stdin
+    } else {
+        Source::File(PathBuf::from(input))
+    }
+}
+
+fn parse_runs(input: &str) -> Result<usize, CliError> {
+    match input.parse::<usize>() {
+        Ok(runs) if runs > 0 => Ok(runs),
+        _ => Err(CliError(format!("Invalid number of runs: {}", input))),
+    }
+}
+
+fn parse_alphabet(input: &str) -> Result<String, CliError> {
+    let mut chars: Vec<_> = input.chars().collect();
+    chars.sort();
+    chars.dedup();
+    if chars.is_empty() || chars.len() != input.chars().count() {
+        return Err(CliError(format!("Invalid alphabet: {}", input)));
+    }
+    Ok(input.to_string())
+}
+
+fn parse_part(input: &str) -> Result<u8, CliError> {
+    match input {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(CliError(format!("Invalid part: {}", input))),
+    }
+}
+
+pub fn parse_args<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut selection = Selection::all();
+    let mut days_given = false;
+    let mut runs = None;
+    let mut paths = false;
+    let mut start = None;
+    let mut end = None;
+    let mut alphabet = None;
+    let mut poker = false;
+    let mut wild = None;
+    // The first options given that only apply to dot or hands
+    let mut dot_option = None;
+    let mut hands_option = None;
+    let mut args = args.into_iter().peekable();
+
+    let command = args.peek().cloned();
+    let bench = command.as_deref() == Some("bench");
+    let dot = command.as_deref() == Some("dot");
+    let hands = command.as_deref() == Some("hands");
+    if bench || dot || hands || command.as_deref() == Some("run") {
+        args.next();
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" | "help" => return Ok(Command::Help),
+            "-p" | "--part" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError(String::from("Missing value for --part")))?;
+                selection.part = Some(parse_part(&value)?);
+            }
+            _ if arg.starts_with("--part=") => {
+                selection.part = Some(parse_part(&arg["--part=".len()..])?);
+            }
+            "-e" | "--example" => selection.input = Source::Example(None),
+            _ if arg.starts_with("--example=") => {
+                selection.input = parse_example(&arg["--example=".len()..])?;
+            }
+            "-i" | "--input" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError(String::from("Missing value for --input")))?;
+                selection.input = parse_input(&value);
+            }
+            _ if arg.starts_with("--input=") => {
+                selection.input = parse_input(&arg["--input=".len()..]);
+            }
+            "-" => selection.input = Source::Stdin,
+            "--runs" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError(String::from("Missing value for --runs")))?;
+                runs = Some(parse_runs(&value)?);
+            }
+            _ if arg.starts_with("--runs=") => {
+                runs = Some(parse_runs(&arg["--runs=".len()..])?);
+            }
+            "--paths" => {
+                paths = true;
+                dot_option.get_or_insert("--paths");
+            }
+            "--start" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError(String::from("Missing value for --start")))?;
+                start = Some(value);
+                dot_option.get_or_insert("--start");
+            }
+            _ if arg.starts_with("--start=") => {
+                start = Some(arg["--start=".len()..].to_string());
+                dot_option.get_or_insert("--start");
+            }
+            "--end" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError(String::from("Missing value for --end")))?;
+                end = Some(value);
+                dot_option.get_or_insert("--end");
+            }
+            _ if arg.starts_with("--end=") => {
+                end = Some(arg["--end=".len()..].to_string());
+                dot_option.get_or_insert("--end");
+            }
+            "--alphabet" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError(String::from("Missing value for --alphabet")))?;
+                alphabet = Some(parse_alphabet(&value)?);
+                dot_option.get_or_insert("--alphabet");
+            }
+            _ if arg.starts_with("--alphabet=") => {
+                alphabet = Some(parse_alphabet(&arg["--alphabet=".len()..])?);
+                dot_option.get_or_insert("--alphabet");
+            }
+            "--poker" => {
+                poker = true;
+                hands_option.get_or_insert("--poker");
+            }
+            "--wild" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError(String::from("Missing value for --wild")))?;
+                wild = Some(value);
+                hands_option.get_or_insert("--wild");
+            }
+            _ if arg.starts_with("--wild=") => {
+                wild = Some(arg["--wild=".len()..].to_string());
+                hands_option.get_or_insert("--wild");
+            }
+            _ if arg.starts_with('-') => {
+                return Err(CliError(format!("Unknown option: {}", arg)));
+            }
+            _ if !days_given => {
+                selection.days = parse_days(&arg)?;
+                days_given = true;
+            }
+            _ => return Err(CliError(format!("Unexpected argument: {}", arg))),
+        }
+    }
+
+    if let Some(option) = dot_option.filter(|_| !dot) {
+        return Err(CliError(format!("{} is only valid with dot", option)));
+    }
+    if let Some(option) = hands_option.filter(|_| !hands) {
+        return Err(CliError(format!("{} is only valid with hands", option)));
+    }
+
+    if hands {
+        return if days_given || selection.part.is_some() || runs.is_some() {
+            Err(CliError(String::from(
+                "hands only takes an input, --poker and --wild",
+            )))
+        } else {
+            Ok(Command::Hands(Hands {
+                input: selection.input,
+                poker,
+                wild,
+            }))
+        };
+    }
+
+    if dot {
+        return if days_given || selection.part.is_some() || runs.is_some() {
+            Err(CliError(String::from(
+                "dot only takes an input, --paths, --start, --end and --alphabet",
+            )))
+        } else {
+            Ok(Command::Dot(Dot {
+                input: selection.input,
+                paths,
+                start,
+                end,
+                alphabet,
+            }))
+        };
+    }
+
+    if selection.input.is_single() && selection.days.len() != 1 {
+        return Err(CliError(format!(
+            "Reading from {} requires selecting a single day",
+            selection.input
+        )));
+    }
+
+    if bench {
+        Ok(Command::Bench(selection, runs.unwrap_or(DEFAULT_RUNS)))
+    } else if runs.is_some() {
+        Err(CliError(String::from("--runs is only valid with bench")))
+    } else {
+        Ok(Command::Run(selection))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(input: &str) -> Vec<String> {
+        input.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("7"), Ok(vec![7]));
+        assert_eq!(parse_days("3..=5"), Ok(vec![3, 4, 5]));
+        assert_eq!(parse_days("3..5"), Ok(vec![3, 4]));
+        assert_eq!(parse_days("9,1..=2,2"), Ok(vec![1, 2, 9]));
+        assert_eq!(parse_days("all").unwrap().len(), 25);
+        assert!(parse_days("0").is_err());
+        assert!(parse_days("26").is_err());
+        assert!(parse_days("5..5").is_err());
+        assert!(parse_days("x").is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(args("")), Ok(Command::Run(Selection::all())));
+        assert_eq!(
+            parse_args(args("run 7 --part 2")),
+            Ok(Command::Run(Selection {
+                days: vec![7],
+                part: Some(2),
+                input: Source::Puzzle,
+            }))
+        );
+        assert_eq!(
+            parse_args(args("1..3 --part=1")),
+            Ok(Command::Run(Selection {
+                days: vec![1, 2],
+                part: Some(1),
+                input: Source::Puzzle,
+            }))
+        );
+        assert_eq!(parse_args(args("run --help")), Ok(Command::Help));
+        assert!(parse_args(args("run 7 --part 3")).is_err());
+        assert!(parse_args(args("run 7 8")).is_err());
+        assert!(parse_args(args("run --verbose")).is_err());
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(
+            parse_args(args("bench 5 --runs 3")),
+            Ok(Command::Bench(
+                Selection {
+                    days: vec![5],
+                    part: None,
+                    input: Source::Puzzle,
+                },
+                3
+            ))
+        );
+        assert_eq!(
+            parse_args(args("bench")),
+            Ok(Command::Bench(Selection::all(), DEFAULT_RUNS))
+        );
+        assert!(parse_args(args("bench --runs 0")).is_err());
+        assert!(parse_args(args("run 5 --runs 3")).is_err());
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let input = |line| match parse_args(args(line)) {
+            Ok(Command::Run(selection)) => Ok(selection.input),
+            Ok(command) => panic!("Unexpected command: {:?}", command),
+            Err(err) => Err(err),
+        };
+        assert_eq!(input("run 1..=9 --example"), Ok(Source::Example(None)));
+        assert_eq!(input("run 8 --example=2"), Ok(Source::Example(Some(2))));
+        assert_eq!(
+            input("run 8 --input other/08.txt"),
+            Ok(Source::File(PathBuf::from("other/08.txt")))
+        );
+        assert_eq!(input("run 8 -"), Ok(Source::Stdin));
+        assert_eq!(input("run 8 --input -"), Ok(Source::Stdin));
+        assert!(input("run 8 --example=0").is_err());
+        assert!(input("run 7..=8 -").is_err());
+        assert!(input("run --input 08.txt").is_err());
+    }
+
+    #[test]
+    fn test_parse_dot() {
+        let dot = |input| Dot {
+            input,
+            paths: false,
+            start: None,
+            end: None,
+            alphabet: None,
+        };
+        assert_eq!(
+            parse_args(args("dot")),
+            Ok(Command::Dot(dot(Source::Puzzle)))
+        );
+        assert_eq!(
+            parse_args(args("dot --paths --example=2")),
+            Ok(Command::Dot(Dot {
+                paths: true,
+                ..dot(Source::Example(Some(2)))
+            }))
+        );
+        assert_eq!(
+            parse_args(args("dot -")),
+            Ok(Command::Dot(dot(Source::Stdin)))
+        );
+        assert_eq!(
+            parse_args(args("dot --start ^AAA$ --end=^ZZZ$ --alphabet abc")),
+            Ok(Command::Dot(Dot {
+                start: Some(String::from("^AAA$")),
+                end: Some(String::from("^ZZZ$")),
+                alphabet: Some(String::from("abc")),
+                ..dot(Source::Puzzle)
+            }))
+        );
+        assert!(parse_args(args("dot 8")).is_err());
+        assert!(parse_args(args("dot --part 1")).is_err());
+        assert!(parse_args(args("dot --alphabet LRL")).is_err());
+        assert!(parse_args(args("dot --start")).is_err());
+        assert_eq!(
+            parse_args(args("run 8 --paths")),
+            Err(CliError(String::from("--paths is only valid with dot")))
+        );
+        assert_eq!(
+            parse_args(args("run 8 --end Z$")),
+            Err(CliError(String::from("--end is only valid with dot")))
+        );
+    }
+
+    #[test]
+    fn test_parse_hands() {
+        assert_eq!(
+            parse_args(args("hands --poker --wild 2J -")),
+            Ok(Command::Hands(Hands {
+                input: Source::Stdin,
+                poker: true,
+                wild: Some(String::from("2J")),
+            }))
+        );
+        assert_eq!(
+            parse_args(args("hands --wild=J")),
+            Ok(Command::Hands(Hands {
+                input: Source::Puzzle,
+                poker: false,
+                wild: Some(String::from("J")),
+            }))
+        );
+        assert!(parse_args(args("hands 7")).is_err());
+        assert!(parse_args(args("hands --wild")).is_err());
+        assert_eq!(
+            parse_args(args("hands --paths")),
+            Err(CliError(String::from("--paths is only valid with dot")))
+        );
+        assert_eq!(
+            parse_args(args("run 7 --poker")),
+            Err(CliError(String::from("--poker is only valid with hands")))
+        );
+        assert_eq!(
+            parse_args(args("dot --wild J")),
+            Err(CliError(String::from("--wild is only valid with hands")))
+        );
+    }
+}
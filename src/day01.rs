@@ -1,90 +1,301 @@
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/01.txt").unwrap();
-    let mut sum = 0;
+use crate::error::Result;
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
+use std::collections::{HashMap, VecDeque};
 
-    for line in input.lines() {
-        let first = line.find(|c: char| c.is_ascii_digit()).unwrap();
-        let last = line.rfind(|c: char| c.is_ascii_digit()).unwrap();
-        let combined =
-            String::from_utf8(vec![line.as_bytes()[first], line.as_bytes()[last]]).unwrap();
-        let number = combined.parse::<u32>().unwrap();
-        sum += number;
-    }
+const DIGITS: [(&str, u32); 10] = [
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+const ENGLISH: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
 
-    println!("01 - Part One: {}", sum);
+/// A word found in a line, with its byte range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub value: u32,
 }
 
-fn parse_slice(slice: &str, f: fn(&str, &str) -> bool) -> Option<char> {
-    if f(slice, "one") {
-        Some('1')
-    } else if f(slice, "two") {
-        Some('2')
-    } else if f(slice, "three") {
-        Some('3')
-    } else if f(slice, "four") {
-        Some('4')
-    } else if f(slice, "five") {
-        Some('5')
-    } else if f(slice, "six") {
-        Some('6')
-    } else if f(slice, "seven") {
-        Some('7')
-    } else if f(slice, "eight") {
-        Some('8')
-    } else if f(slice, "nine") {
-        Some('9')
-    } else {
-        None
-    }
+#[derive(Default)]
+struct Node {
+    next: HashMap<char, usize>,
+    fail: usize,
+    // The value and byte length of every word ending here, including the
+    // words ending at the nodes down the failure links
+    words: Vec<(u32, usize)>,
 }
 
-fn find_first(input: &str) -> Option<char> {
-    for index in 0..(input.len()) {
-        let c = input.chars().nth(index)?;
-        if c.is_ascii_digit() {
-            return Some(c);
+/// An Aho-Corasick automaton finding every occurrence of a table of words,
+/// overlapping ones included, in a single pass over a line.
+pub struct Scanner {
+    nodes: Vec<Node>,
+}
+
+impl Scanner {
+    pub fn new<'a>(words: impl IntoIterator<Item = (&'a str, u32)>) -> Self {
+        let mut nodes = vec![Node::default()];
+
+        for (word, value) in words {
+            let mut node = 0;
+            for c in word.chars() {
+                node = match nodes[node].next.get(&c) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(Node::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].next.insert(c, next);
+                        next
+                    }
+                };
+            }
+            nodes[node].words.push((value, word.len()));
         }
 
-        let end = std::cmp::min(index + 5, input.len());
-        let slice = &input[index..end];
-        let numeral = parse_slice(slice, |x: &str, y: &str| str::starts_with(x, y));
-        if let Some(c) = numeral {
-            return Some(c);
+        // Breadth first, so the failure link of every shallower node is known
+        let mut queue: VecDeque<usize> = nodes[0].next.values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let next: Vec<_> = nodes[node].next.iter().map(|(&c, &n)| (c, n)).collect();
+            for (c, child) in next {
+                let mut fail = nodes[node].fail;
+                let fail = loop {
+                    if let Some(&n) = nodes[fail].next.get(&c) {
+                        break n;
+                    } else if fail == 0 {
+                        break 0;
+                    }
+                    fail = nodes[fail].fail;
+                };
+                nodes[child].fail = fail;
+                let inherited = nodes[fail].words.clone();
+                nodes[child].words.extend(inherited);
+                queue.push_back(child);
+            }
         }
+
+        Scanner { nodes }
     }
 
-    None
-}
+    /// A scanner for the digits `0` to `9`.
+    pub fn digits() -> Self {
+        Self::new(DIGITS)
+    }
+
+    /// A scanner for the digits `1` to `9`, also spelled out in English.
+    pub fn english() -> Self {
+        Self::new(DIGITS.into_iter().skip(1).chain(ENGLISH))
+    }
 
-fn find_last(input: &str) -> Option<char> {
-    for index in (0..(input.len())).rev() {
-        let c = input.chars().nth(index)?;
-        if c.is_ascii_digit() {
-            return Some(c);
+    /// Every match in `line`, ordered by where they end.
+    pub fn matches(&self, line: &str) -> Vec<Match> {
+        let mut matches = vec![];
+        let mut node = 0;
+
+        for (index, c) in line.char_indices() {
+            node = loop {
+                if let Some(&next) = self.nodes[node].next.get(&c) {
+                    break next;
+                } else if node == 0 {
+                    break 0;
+                }
+                node = self.nodes[node].fail;
+            };
+
+            let end = index + c.len_utf8();
+            for &(value, len) in &self.nodes[node].words {
+                matches.push(Match {
+                    start: end - len,
+                    end,
+                    value,
+                });
+            }
         }
 
-        let start = std::cmp::max(index as isize - 4, 0) as usize;
-        let slice = &input[start..index + 1];
-        let numeral = parse_slice(slice, |x: &str, y: &str| str::ends_with(x, y));
-        if let Some(c) = numeral {
-            return Some(c);
+        matches
+    }
+}
+
+/// The first and last digits found in a line of the calibration document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Calibration<'a> {
+    /// 1-based line number
+    pub line: usize,
+    pub text: &'a str,
+    pub first: Match,
+    pub last: Match,
+}
+
+impl Calibration<'_> {
+    /// The first and last digits combined into a two digit number.
+    pub fn value(&self) -> u32 {
+        self.first.value * 10 + self.last.value
+    }
+}
+
+/// The calibration values of every line, and the lines without any digit.
+pub struct Audit<'a> {
+    pub calibrations: Vec<Calibration<'a>>,
+    /// 1-based line numbers and text of the lines without digits
+    pub missing: Vec<(usize, &'a str)>,
+}
+
+impl Audit<'_> {
+    pub fn sum(&self) -> u32 {
+        self.calibrations.iter().map(Calibration::value).sum()
+    }
+}
+
+fn calibration<'a>(scanner: &Scanner, line: usize, text: &'a str) -> Option<Calibration<'a>> {
+    let matches = scanner.matches(text);
+    Some(Calibration {
+        line,
+        text,
+        first: *matches.iter().min_by_key(|m| m.start)?,
+        last: *matches.iter().max_by_key(|m| m.start)?,
+    })
+}
+
+/// Find the calibration value of every line, noting the lines without digits
+/// rather than failing on them.
+pub fn audit<'a>(input: &'a str, scanner: &Scanner) -> Audit<'a> {
+    let mut audit = Audit {
+        calibrations: vec![],
+        missing: vec![],
+    };
+
+    for (index, text) in input.lines().enumerate() {
+        match calibration(scanner, index + 1, text) {
+            Some(calibration) => audit.calibrations.push(calibration),
+            None => audit.missing.push((index + 1, text)),
         }
     }
 
-    None
+    audit
+}
+
+fn sum_calibrations(input: &str, scanner: &Scanner, message: &str) -> Result<u32> {
+    let audit = audit(input, scanner);
+    if let Some(&(_, text)) = audit.missing.first() {
+        return Err(Parser::new(input).error(text, message));
+    }
+
+    Ok(audit.sum())
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input<'a> = &'a str;
+    type View<'a> = str;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        Ok(input)
+    }
+
+    fn part_one(&self, input: &str) -> Result<Answer> {
+        let sum = sum_calibrations(input, &Scanner::digits(), "No digits in line")?;
+        Ok(sum.into())
+    }
+
+    fn part_two(&self, input: &str) -> Result<Answer> {
+        let message = "No digits or spelled out numbers in line";
+        let sum = sum_calibrations(input, &Scanner::english(), message)?;
+        Ok(sum.into())
+    }
 }
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/01.txt").unwrap();
-    let mut sum = 0;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for line in input.lines() {
-        let first = find_first(line).unwrap();
-        let last = find_last(line).unwrap();
-        let combined = format!("{}{}", first, last);
-        let number = combined.parse::<u32>().unwrap();
-        sum += number;
+    fn values(scanner: &Scanner, line: &str) -> Vec<u32> {
+        scanner.matches(line).iter().map(|m| m.value).collect()
     }
 
-    println!("01 - Part Two: {}", sum);
+    #[test]
+    fn test_overlapping_words() {
+        let scanner = Scanner::english();
+        assert_eq!(values(&scanner, "eightwothree"), [8, 2, 3]);
+        assert_eq!(values(&scanner, "oneight7sevenine"), [1, 8, 7, 7, 9]);
+        assert_eq!(values(&scanner, "twone"), [2, 1]);
+        assert_eq!(values(&scanner, "ninine"), [9]);
+        assert!(values(&scanner, "zero0").is_empty());
+
+        let matches = scanner.matches("sevenine");
+        assert_eq!(
+            matches,
+            [
+                Match {
+                    start: 0,
+                    end: 5,
+                    value: 7
+                },
+                Match {
+                    start: 4,
+                    end: 8,
+                    value: 9
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_ascii_lines() {
+        let scanner = Scanner::english();
+        let calibration = calibration(&scanner, 1, "ébone→2ü").unwrap();
+        assert_eq!(calibration.value(), 12);
+
+        let matches = scanner.matches("→nine");
+        assert_eq!((matches[0].start, matches[0].end), (3, 7));
+    }
+
+    #[test]
+    fn test_word_tables() {
+        let french = [("un", 1), ("deux", 2), ("trois", 3), ("zéro", 0)];
+        let scanner = Scanner::new(DIGITS.into_iter().chain(french));
+        assert_eq!(values(&scanner, "zérotroisdeuxun"), [0, 3, 2, 1]);
+        assert_eq!(calibration(&scanner, 1, "0deux").unwrap().value(), 2);
+
+        // A word inside another is found on its own too
+        let scanner = Scanner::new([("seven", 7), ("even", 2), ("n", 1)]);
+        assert_eq!(values(&scanner, "seven"), [7, 2, 1]);
+    }
+
+    #[test]
+    fn test_audit() {
+        let input = "two1nine\nabcdef\nxtwone3four\n\n7pqrstsixteen\n";
+        let audit = audit(input, &Scanner::english());
+        assert_eq!(audit.missing, [(2, "abcdef"), (4, "")]);
+
+        let values: Vec<_> = audit.calibrations.iter().map(|c| c.value()).collect();
+        assert_eq!(values, [29, 24, 76]);
+        assert_eq!(audit.sum(), 129);
+
+        let calibration = audit.calibrations[1];
+        assert_eq!((calibration.line, calibration.text), (3, "xtwone3four"));
+        assert_eq!((calibration.first.start, calibration.first.end), (1, 4));
+        assert_eq!((calibration.last.start, calibration.last.end), (7, 11));
+
+        let error = sum_calibrations(input, &Scanner::digits(), "No digits in line");
+        assert_eq!(error.unwrap_err().line, Some(2));
+    }
 }
@@ -1,79 +1,147 @@
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/02.txt").unwrap();
-    let mut sum = 0;
-
-    for line in input.lines() {
-        let (idstr, cubes) = line.split_once(": ").unwrap();
-        let id = idstr[5..].parse::<u8>().unwrap();
-        let mut possible = true;
-
-        for c in cubes.split(&[',', ';']) {
-            let (numberstr, color) = c.trim().split_once(' ').unwrap();
-            let number = numberstr.parse::<u8>().unwrap();
-            match color {
-                "red" => {
-                    if number > 12 {
-                        possible = false
-                    }
-                }
-                "green" => {
-                    if number > 13 {
-                        possible = false
-                    }
-                }
-                "blue" => {
-                    if number > 14 {
-                        possible = false
-                    }
-                }
-                _ => (),
-            }
-        }
+use crate::error::Result;
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
+use std::collections::BTreeMap;
+
+/// The number of cubes of each color in a bag.
+pub type Bag<'a> = BTreeMap<&'a str, u32>;
+
+const BAG: [(&str, u32); 3] = [("red", 12), ("green", 13), ("blue", 14)];
+
+const COLORS: [&str; 3] = ["red", "green", "blue"];
+
+/// The cubes revealed from the bag at once.
+pub struct Round<'a> {
+    cubes: Vec<(u32, &'a str)>,
+}
+
+pub struct Game<'a> {
+    id: u32,
+    rounds: Vec<Round<'a>>,
+}
 
-        if possible {
-            sum += id as u32
+impl Game<'_> {
+    /// Whether every round could have been drawn from `bag`.
+    pub fn is_possible(&self, bag: &Bag) -> bool {
+        self.rounds
+            .iter()
+            .flat_map(|round| &round.cubes)
+            .all(|(number, color)| bag.get(color).is_some_and(|n| number <= n))
+    }
+
+    /// The fewest cubes of each color the bag could have held.
+    pub fn minimum_bag(&self) -> Bag<'_> {
+        let mut bag = Bag::new();
+        for &(number, color) in self.rounds.iter().flat_map(|round| &round.cubes) {
+            let count = bag.entry(color).or_default();
+            *count = (*count).max(number);
         }
+        bag
     }
 
-    println!("02 - Part One: {}", sum);
+    /// The product of the minimum number of cubes of each of `colors`,
+    /// which is zero if one was never seen.
+    pub fn power(&self, colors: &[&str]) -> u64 {
+        let bag = self.minimum_bag();
+        colors
+            .iter()
+            .map(|color| bag.get(color).copied().unwrap_or(0) as u64)
+            .product()
+    }
 }
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/02.txt").unwrap();
-    let mut sum = 0;
-
-    for line in input.lines() {
-        let (_, cubes) = line.split_once(": ").unwrap();
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
-
-        for c in cubes.split(&[',', ';']) {
-            let (numberstr, color) = c.trim().split_once(' ').unwrap();
-            let number = numberstr.parse::<u32>().unwrap();
-            match color {
-                "red" => {
-                    if number > red {
-                        red = number
-                    }
-                }
-                "green" => {
-                    if number > green {
-                        green = number
-                    }
-                }
-                "blue" => {
-                    if number > blue {
-                        blue = number
-                    }
-                }
-                _ => (),
-            }
+fn parse_round<'a>(parser: &Parser, text: &'a str) -> Result<Round<'a>> {
+    let mut cubes: Vec<(u32, &str)> = vec![];
+    for c in text.split(',') {
+        let (numberstr, color) = parser.split_once(c.trim(), " ")?;
+        if cubes.iter().any(|&(_, seen)| seen == color) {
+            return Err(parser.error(color, "Color repeated in round"));
         }
+        cubes.push((parser.parse::<u32>(numberstr)?, color));
+    }
+
+    Ok(Round { cubes })
+}
+
+fn parse_game<'a>(parser: &Parser, line: &'a str) -> Result<Game<'a>> {
+    let (idstr, rounds_str) = parser.split_once(line, ": ")?;
+    let id = parser.parse::<u32>(parser.strip_prefix(idstr, "Game ")?)?;
+    let rounds = rounds_str
+        .split(';')
+        .map(|round| parse_round(parser, round))
+        .collect::<Result<_>>()?;
+
+    Ok(Game { id, rounds })
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input<'a> = Vec<Game<'a>>;
+    type View<'a> = [Game<'a>];
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        let parser = Parser::new(input);
+        input
+            .lines()
+            .map(|line| parse_game(&parser, line))
+            .collect()
+    }
+
+    fn part_one(&self, games: &[Game]) -> Result<Answer> {
+        let bag = Bag::from(BAG);
+        let sum: u32 = games
+            .iter()
+            .filter(|game| game.is_possible(&bag))
+            .map(|game| game.id)
+            .sum();
+
+        Ok(sum.into())
+    }
 
-        let power = red * green * blue;
-        sum += power;
+    fn part_two(&self, games: &[Game]) -> Result<Answer> {
+        let sum: u64 = games.iter().map(|game| game.power(&COLORS)).sum();
+
+        Ok(sum.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_games() {
+        let input = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 5 purple, 2 red; 7 purple
+";
+        let games = Day02.parse(input).unwrap();
+        assert_eq!(games[0].rounds.len(), 3);
+
+        let bag = Bag::from([("red", 4), ("green", 2), ("blue", 6)]);
+        assert!(games[0].is_possible(&bag));
+        assert!(!games[1].is_possible(&bag));
+        assert!(!games[2].is_possible(&bag));
+        assert!(games[2].is_possible(&Bag::from([("red", 2), ("purple", 7)])));
+
+        assert_eq!(games[0].minimum_bag(), bag);
+        assert_eq!(
+            games[2].minimum_bag(),
+            Bag::from([("purple", 7), ("red", 2)])
+        );
+        assert_eq!(games[0].power(&COLORS), 48);
+        assert_eq!(games[1].power(&COLORS), 12);
+        assert_eq!(games[2].power(&COLORS), 0);
+        assert_eq!(games[2].power(&["red", "purple"]), 14);
     }
 
-    println!("02 - Part Two: {}", sum);
+    #[test]
+    fn test_repeated_color() {
+        let input = "Game 1: 3 blue, 4 red, 1 blue\n";
+        let error = Day02.parse(input).err().unwrap();
+        assert_eq!(error.message, "Color repeated in round");
+        assert_eq!(error.column, Some(26));
+    }
 }
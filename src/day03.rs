@@ -1,9 +1,9 @@
+use crate::error::Result;
+use crate::grid::{Coord, Grid};
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
 use regex::Regex;
-
-struct Coord {
-    row: usize,
-    col: usize,
-}
+use std::collections::BTreeMap;
 
 struct PartNumber {
     coord: Coord,
@@ -11,137 +11,229 @@ struct PartNumber {
     number: u32,
 }
 
-impl PartNumber {
-    // Generate the perimeter given a number in a grid of given size
-    pub fn perimeter(&self, width: usize, height: usize) -> Vec<Coord> {
-        let mut coords: Vec<Coord> = vec![];
-        let above = self.coord.row as isize - 1;
-        let below = self.coord.row as isize + 1;
-        let left = self.coord.col as isize - 1;
-        let right = self.coord.col as isize + self.span as isize;
-
-        if above >= 0 {
-            for x in left..right + 1 {
-                if x >= 0 && x < width as isize {
-                    coords.push(Coord {
-                        row: above as usize,
-                        col: x as usize,
-                    });
-                }
-            }
-        }
+/// A symbol in the schematic and the numbers adjacent to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub coord: Coord,
+    pub symbol: char,
+    pub numbers: Vec<u32>,
+}
+
+pub struct Schematic {
+    grid: Grid<char>,
+    numbers: Vec<PartNumber>,
+    // The index in `numbers` of the number covering each cell
+    index: Grid<Option<usize>>,
+}
+
+impl Schematic {
+    fn is_symbol(c: char) -> bool {
+        c != '.' && !c.is_ascii_digit()
+    }
+
+    /// Every symbol in the schematic with its position, row by row.
+    pub fn symbols(&self) -> impl Iterator<Item = (Coord, char)> + '_ {
+        self.grid.rows().enumerate().flat_map(|(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, &c)| Self::is_symbol(c))
+                .map(move |(col, &c)| (Coord::new(row, col), c))
+        })
+    }
 
-        if left >= 0 {
-            coords.push(Coord {
-                row: self.coord.row,
-                col: left as usize,
+    fn touching(&self, coord: Coord) -> Vec<usize> {
+        let mut indices: Vec<_> = self
+            .grid
+            .neighbours8(coord)
+            .filter_map(|c| *self.index.get(c)?)
+            .collect();
+        indices.sort();
+        indices.dedup();
+        indices
+    }
+
+    /// The numbers adjacent to the cell at `coord`, diagonals included, in
+    /// the order they appear in the schematic.
+    pub fn numbers_touching(&self, coord: Coord) -> Vec<u32> {
+        self.touching(coord)
+            .into_iter()
+            .map(|i| self.numbers[i].number)
+            .collect()
+    }
+
+    /// Every symbol with its adjacent numbers, grouped by symbol character.
+    pub fn by_symbol(&self) -> BTreeMap<char, Vec<Symbol>> {
+        let mut symbols: BTreeMap<char, Vec<Symbol>> = BTreeMap::new();
+        for (coord, symbol) in self.symbols() {
+            symbols.entry(symbol).or_default().push(Symbol {
+                coord,
+                symbol,
+                numbers: self.numbers_touching(coord),
             });
         }
+        symbols
+    }
 
-        if right < width as isize {
-            coords.push(Coord {
-                row: self.coord.row,
-                col: right as usize,
+    /// The symbols of the given character adjacent to exactly `count` numbers.
+    pub fn with_neighbours(&self, symbol: char, count: usize) -> Vec<Symbol> {
+        let mut symbols = self.by_symbol().remove(&symbol).unwrap_or_default();
+        symbols.retain(|s| s.numbers.len() == count);
+        symbols
+    }
+
+    /// The sum of the numbers adjacent to each symbol character. A number
+    /// touching several symbols of the same character is counted for each.
+    #[cfg(test)]
+    pub fn sum_by_symbol(&self) -> BTreeMap<char, u64> {
+        self.by_symbol()
+            .into_iter()
+            .map(|(c, symbols)| {
+                let sum = symbols
+                    .iter()
+                    .flat_map(|s| &s.numbers)
+                    .map(|&n| n as u64)
+                    .sum();
+                (c, sum)
             })
-        }
+            .collect()
+    }
 
-        if below < height as isize {
-            for x in left..right + 1 {
-                if x >= 0 && x < width as isize {
-                    coords.push(Coord {
-                        row: below as usize,
-                        col: x as usize,
-                    });
-                }
+    /// Whether each number, in order, is adjacent to a symbol.
+    fn part_numbers(&self) -> Vec<bool> {
+        let mut parts = vec![false; self.numbers.len()];
+        for (coord, _) in self.symbols() {
+            for i in self.touching(coord) {
+                parts[i] = true;
             }
         }
-
-        coords
+        parts
     }
 }
 
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/03.txt").unwrap();
-    let mut grid: Vec<Vec<char>> = vec![];
+fn parse_schematic(input: &str) -> Result<Schematic> {
+    let parser = Parser::new(input);
+    let grid = Grid::parse(input)?;
+    let mut index = Grid::new(grid.width(), grid.height(), None);
     let mut numbers: Vec<PartNumber> = vec![];
     let digits_re = Regex::new(r"(\d+)+").unwrap();
-    let width = input.find('\n').unwrap();
-    let height = input.lines().count();
-
-    for (index, line) in input.trim().lines().enumerate() {
-        grid.push(line.chars().collect());
-        let matches: Vec<_> = digits_re.find_iter(line).collect();
-        for m in matches {
-            numbers.push(PartNumber {
-                coord: Coord {
-                    row: index,
-                    col: m.start(),
-                },
-                span: m.end() - m.start(),
-                number: m.as_str().parse().unwrap(),
-            })
+
+    for (row, line) in input.lines().take(grid.height()).enumerate() {
+        for m in digits_re.find_iter(line) {
+            let number = PartNumber {
+                coord: Coord::new(row, line[..m.start()].chars().count()),
+                span: m.as_str().chars().count(),
+                number: parser.parse(m.as_str())?,
+            };
+            for col in number.coord.col..number.coord.col + number.span {
+                index[Coord::new(row, col)] = Some(numbers.len());
+            }
+            numbers.push(number);
         }
     }
 
-    let sum = numbers
-        .iter()
-        .filter(|n| {
-            n.perimeter(width, height)
-                .iter()
-                .any(|c| grid[c.row][c.col] != '.')
-        })
-        .fold(0, |sum, n| sum + n.number);
-
-    println!("03 - Part One: {}", sum);
+    Ok(Schematic {
+        grid,
+        numbers,
+        index,
+    })
 }
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/03.txt").unwrap();
-    let mut grid: Vec<Vec<char>> = vec![];
-    let mut numbers: Vec<PartNumber> = vec![];
-    let mut gears: Vec<Coord> = vec![];
-    let digits_re = Regex::new(r"(\d+)+").unwrap();
-    let gear_re = Regex::new(r"(\*)").unwrap();
-    let width = input.find('\n').unwrap();
-    let height = input.lines().count();
-
-    for (index, line) in input.trim().lines().enumerate() {
-        grid.push(line.chars().collect());
-        let matches: Vec<_> = digits_re.find_iter(line).collect();
-        for m in matches {
-            numbers.push(PartNumber {
-                coord: Coord {
-                    row: index,
-                    col: m.start(),
-                },
-                span: m.end() - m.start(),
-                number: m.as_str().parse().unwrap(),
-            })
-        }
+pub struct Day03;
 
-        let gear_matches: Vec<_> = gear_re.find_iter(line).collect();
-        for g in gear_matches {
-            gears.push(Coord {
-                row: index,
-                col: g.start(),
-            })
-        }
+impl Solution for Day03 {
+    type Input<'a> = Schematic;
+    type View<'a> = Schematic;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        parse_schematic(input)
     }
 
-    let mut sum = 0;
-    for gear in gears {
-        let connected: Vec<_> = numbers
+    fn part_one(&self, schematic: &Schematic) -> Result<Answer> {
+        let sum: u32 = schematic
+            .numbers
             .iter()
-            .filter(|n| {
-                n.perimeter(width, height)
-                    .iter()
-                    .any(|c| c.row == gear.row && c.col == gear.col)
-            })
-            .collect();
-        if connected.len() == 2 {
-            sum += connected[0].number * connected[1].number;
-        }
+            .zip(schematic.part_numbers())
+            .filter(|(_, part)| *part)
+            .map(|(n, _)| n.number)
+            .sum();
+
+        Ok(sum.into())
+    }
+
+    fn part_two(&self, schematic: &Schematic) -> Result<Answer> {
+        let sum: u64 = schematic
+            .with_neighbours('*', 2)
+            .iter()
+            .map(|gear| gear.numbers.iter().map(|&n| n as u64).product::<u64>())
+            .sum();
+
+        Ok(sum.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers_touching() {
+        let input = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+";
+        let schematic = parse_schematic(input).unwrap();
+        assert_eq!(schematic.numbers_touching(Coord::new(1, 3)), [467, 35]);
+        assert_eq!(schematic.numbers_touching(Coord::new(3, 6)), [633]);
+        assert_eq!(schematic.numbers_touching(Coord::new(4, 3)), [617]);
+        assert_eq!(schematic.numbers_touching(Coord::new(1, 8)), [114, 633]);
+        assert!(schematic.numbers_touching(Coord::new(2, 0)).is_empty());
+        assert_eq!(schematic.part_numbers(), [true, false, true, true, true]);
     }
 
-    println!("03 - Part Two: {}", sum);
+    #[test]
+    fn test_symbols() {
+        let input = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+        let schematic = parse_schematic(input).unwrap();
+        let symbols = schematic.by_symbol();
+        assert_eq!(symbols.keys().collect::<String>(), "#$*+");
+        assert_eq!(symbols[&'*'].len(), 3);
+        assert_eq!(
+            symbols[&'$'],
+            [Symbol {
+                coord: Coord::new(8, 3),
+                symbol: '$',
+                numbers: vec![664],
+            }]
+        );
+
+        let gears = schematic.with_neighbours('*', 2);
+        let numbers: Vec<_> = gears.iter().map(|g| g.numbers.clone()).collect();
+        assert_eq!(numbers, [[467, 35], [755, 598]]);
+        assert_eq!(schematic.with_neighbours('*', 1)[0].coord, Coord::new(4, 3));
+        assert!(schematic.with_neighbours('#', 2).is_empty());
+
+        let sums = schematic.sum_by_symbol();
+        let expected = [
+            ('#', 633),
+            ('$', 664),
+            ('*', 467 + 35 + 617 + 755 + 598),
+            ('+', 592),
+        ];
+        assert_eq!(sums, BTreeMap::from(expected));
+    }
 }
@@ -1,98 +1,214 @@
+use crate::error::{Error, Result};
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
+use num::{BigUint, Zero};
 use regex::Regex;
 use std::collections::HashSet;
 
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/04.txt").unwrap();
-    let whitespace_re = Regex::new(r"\s+").unwrap();
-    let mut sum = 0;
+#[derive(Clone, Debug)]
+pub struct Scratchcard {
+    id: u32,
+    winning: HashSet<u64>,
+    held: HashSet<u64>,
+}
 
-    for line in input.lines() {
-        let (_, scratchcard) = line.split_once(": ").unwrap();
-        let (winning_str, numbers_str) = scratchcard.split_once(" | ").unwrap();
-        let mut score = 0;
+impl Scratchcard {
+    fn parse(parser: &Parser, number_re: &Regex, line: &str) -> Result<Self> {
+        let (card_id_str, scratchcard) = parser.split_once(line, ": ")?;
+        let card_id = number_re
+            .find(card_id_str)
+            .ok_or_else(|| parser.error(card_id_str, "Expected a card number"))?;
+        let id = parser.parse::<u32>(card_id.as_str())?;
+        let (winning_str, held_str) = parser.split_once(scratchcard, " | ")?;
+
+        Ok(Scratchcard {
+            id,
+            winning: parse_side(parser, winning_str)?,
+            held: parse_side(parser, held_str)?,
+        })
+    }
 
-        let winning: HashSet<_> = whitespace_re
-            .split(winning_str.trim())
-            .map(|w| w.parse::<u8>().unwrap())
-            .collect();
-        let numbers: HashSet<_> = whitespace_re
-            .split(numbers_str.trim())
-            .map(|n| n.parse::<u8>().unwrap())
-            .collect();
+    /// How many of the held numbers are winning numbers.
+    pub fn matches(&self) -> usize {
+        self.held.intersection(&self.winning).count()
+    }
+}
 
-        for _ in numbers.intersection(&winning) {
-            if score == 0 {
-                score += 1
-            } else {
-                score *= 2
-            }
+fn parse_side(parser: &Parser, text: &str) -> Result<HashSet<u64>> {
+    let mut numbers = HashSet::new();
+    for number_str in text.split_whitespace() {
+        if !numbers.insert(parser.parse(number_str)?) {
+            return Err(parser.error(number_str, "Duplicate number"));
         }
-
-        sum += score;
     }
-
-    println!("04 - Part One: {}", sum);
+    Ok(numbers)
 }
 
-#[derive(Clone, Debug)]
-struct Card {
-    id: u8,
-    win_count: u8,
+fn parse_scratchcards(input: &str) -> Result<Vec<Scratchcard>> {
+    let parser = Parser::new(input);
+    let number_re = Regex::new(r"(\d+)").unwrap();
+    input
+        .lines()
+        .map(|line| Scratchcard::parse(&parser, &number_re, line))
+        .collect()
 }
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/04.txt").unwrap();
-    let number_re = Regex::new(r"(\d+)").unwrap();
-    let whitespace_re = Regex::new(r"\s+").unwrap();
-    let mut cards: Vec<Card> = vec![];
+/// The number of copies of each card held once every won copy has been
+/// scratched, including the original. Copies of cards past the end of the
+/// table are not won.
+pub fn copies(cards: &[Scratchcard]) -> Vec<BigUint> {
+    let total = cards.len();
+
+    // Every copy of a card wins one copy of each of the following cards, and
+    // cards only win later ones, so a single pass settles all the counts.
+    // `won` is the number of copies the cards so far win of the current one,
+    // and `expired` what stops being won at each card.
+    let mut copies = Vec::with_capacity(total);
+    let mut won = BigUint::zero();
+    let mut expired = vec![BigUint::zero(); total + 1];
+    for (index, card) in cards.iter().enumerate() {
+        won -= std::mem::take(&mut expired[index]);
+        let count = &won + 1u32;
+        let last = (index + card.matches()).min(total - 1);
+        if last > index {
+            won += &count;
+            expired[last + 1] += &count;
+        }
+        copies.push(count);
+    }
 
-    for line in input.lines() {
-        let (card_id_str, scratchcard) = line.split_once(": ").unwrap();
-        let card_id = number_re
-            .find(card_id_str)
-            .unwrap()
-            .as_str()
-            .parse::<u8>()
-            .unwrap();
-        let (winning_str, numbers_str) = scratchcard.split_once(" | ").unwrap();
-
-        let winning: HashSet<_> = whitespace_re
-            .split(winning_str.trim())
-            .map(|w| w.parse::<u8>().unwrap())
-            .collect();
-        let numbers: HashSet<_> = whitespace_re
-            .split(numbers_str.trim())
-            .map(|n| n.parse::<u8>().unwrap())
-            .collect();
+    copies
+}
 
-        let win_count = numbers.intersection(&winning).count() as u8;
+pub struct Day04;
 
-        cards.push(Card {
-            id: card_id,
-            win_count,
-        })
+impl Solution for Day04 {
+    type Input<'a> = Vec<Scratchcard>;
+    type View<'a> = [Scratchcard];
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        parse_scratchcards(input)
     }
 
-    let mut additional_cards: Vec<Card> = vec![];
-    let mut index = 0;
-    loop {
-        for card in &cards[index..] {
-            if card.win_count > 0 {
-                let pos = card.id as usize;
-                let new = &cards[pos..pos + card.win_count as usize];
-                additional_cards.extend_from_slice(new);
-            }
-            index += 1;
+    fn part_one(&self, cards: &[Scratchcard]) -> Result<Answer> {
+        let mut sum: u64 = 0;
+
+        for card in cards {
+            let score = match card.matches() {
+                0 => 0,
+                matches => 1u64
+                    .checked_shl(matches as u32 - 1)
+                    .ok_or_else(|| Error::new(format!("Card {} scores too much", card.id)))?,
+            };
+            sum = sum
+                .checked_add(score)
+                .ok_or_else(|| Error::new("Total score is too large"))?;
         }
 
-        if additional_cards.is_empty() {
-            break;
-        }
+        Ok(sum.into())
+    }
+
+    fn part_two(&self, cards: &[Scratchcard]) -> Result<Answer> {
+        let sum: BigUint = copies(cards).into_iter().sum();
+        Ok(sum.into())
+    }
+}
 
-        cards.extend_from_slice(&additional_cards);
-        additional_cards.clear();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::One;
+
+    #[test]
+    fn test_copies() {
+        let input = "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+        let cards = parse_scratchcards(input).unwrap();
+        let expected: Vec<BigUint> = [1u32, 2, 4, 8, 14, 1].map(BigUint::from).into();
+        assert_eq!(copies(&cards), expected);
     }
 
-    let sum = cards.len();
-    println!("04 - Part Two: {}", sum);
+    fn chain(len: u32, matches: u32) -> Vec<Scratchcard> {
+        (1..=len)
+            .map(|id| {
+                let numbers: HashSet<_> = (0..matches as u64).collect();
+                Scratchcard {
+                    id,
+                    winning: numbers.clone(),
+                    held: numbers,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_copies_of_many_cards() {
+        let counts = copies(&chain(5000, 1));
+        assert!(counts.iter().zip(1u32..).all(|(n, id)| *n == id.into()));
+        let sum = Day04.part_two(&chain(5000, 1)).unwrap();
+        assert_eq!(sum.to_string(), "12502500");
+
+        // Card n is won by every copy of the cards before it, doubling the
+        // count each time, and the wins past the last card are dropped
+        let counts = copies(&chain(200, 199));
+        assert_eq!(counts[199], BigUint::one() << 199);
+        let sum = Day04.part_two(&chain(200, 1000)).unwrap();
+        let expected: BigUint = (BigUint::one() << 200) - 1u32;
+        assert_eq!(sum.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_copies_of_uneven_cards() {
+        // Each card adds its copies to the following ones one by one
+        let cards: Vec<_> = (1..=60)
+            .map(|id| {
+                let numbers: HashSet<_> = (0..(id * 7 % 11) as u64).collect();
+                Scratchcard {
+                    id,
+                    winning: numbers.clone(),
+                    held: numbers,
+                }
+            })
+            .collect();
+        let mut expected = vec![BigUint::one(); cards.len()];
+        for (index, card) in cards.iter().enumerate() {
+            let count = expected[index].clone();
+            let last = (index + card.matches()).min(cards.len() - 1);
+            for next in &mut expected[index + 1..=last] {
+                *next += &count;
+            }
+        }
+        assert_eq!(copies(&cards), expected);
+    }
+
+    #[test]
+    fn test_wide_scratchcards() {
+        let input = "\
+Card 1: 1000 70000 | 70000 5 1000
+Card  2: 4294967296 | 4294967296 7
+";
+        let cards = parse_scratchcards(input).unwrap();
+        assert_eq!(cards[0].matches(), 2);
+        assert_eq!(cards[1].matches(), 1);
+
+        let input = "Card 1: 1 2 3 | 4 5\nCard 2: 7 8 | 9 10 9\n";
+        let error = parse_scratchcards(input).unwrap_err();
+        assert_eq!(error.message, "Duplicate number");
+        assert_eq!((error.line, error.column), (Some(2), Some(20)));
+
+        // Cards win copies of the cards after them in the table, whatever
+        // their IDs
+        let input = "Card 1000: 1 2 | 1 2\nCard 5: 3 | 3\nCard 9: 4 | 5\n";
+        let cards = parse_scratchcards(input).unwrap();
+        let ids: Vec<_> = cards.iter().map(|card| card.id).collect();
+        assert_eq!(ids, [1000, 5, 9]);
+        assert_eq!(Day04.part_one(&cards).unwrap().to_string(), "3");
+        assert_eq!(Day04.part_two(&cards).unwrap().to_string(), "7");
+    }
 }
@@ -1,14 +1,14 @@
-use rayon::prelude::*;
-
-fn parse_seeds(input: &str) -> Vec<u64> {
-    let (_, number_str) = input.split_once(": ").unwrap();
-    number_str
-        .split(' ')
-        .map(|n| n.parse::<u64>().unwrap())
-        .collect()
+use crate::error::{Error, Result};
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
+use std::collections::{HashMap, VecDeque};
+
+fn parse_seeds(parser: &Parser, input: &str) -> Result<Vec<u64>> {
+    let (_, number_str) = parser.split_once(input, ": ")?;
+    parser.numbers(number_str)
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 struct Range {
     src: u64,
     dst: u64,
@@ -29,21 +29,35 @@ impl Range {
     }
 }
 
-#[derive(Debug)]
-struct Map {
+#[derive(Debug, PartialEq, Eq)]
+pub struct Map {
+    from: String,
+    to: String,
     ranges: Vec<Range>,
 }
 
 impl Map {
-    pub fn new(input: &[&str]) -> Self {
+    pub fn parse(parser: &Parser, input: &[&str]) -> Result<Self> {
+        let header = parser.strip_suffix(input[0], " map:")?;
+        let (from, to) = parser.split_once(header, "-to-")?;
+
         let mut ranges = vec![];
         for line in input.iter().skip(1) {
-            let numbers: Vec<u64> = line.split(' ').map(|n| n.parse::<u64>().unwrap()).collect();
-            let (dst, src, rng) = (numbers[0], numbers[1], numbers[2]);
+            let numbers: Vec<u64> = parser.numbers(line)?;
+            let [dst, src, rng] = numbers[..] else {
+                return Err(parser.error(line, "Expected three numbers"));
+            };
+            if src.checked_add(rng).is_none() || dst.checked_add(rng).is_none() {
+                return Err(parser.error(line, "Range is out of bounds"));
+            }
             let range = Range { src, dst, rng };
             ranges.push(range);
         }
-        Self { ranges }
+        Ok(Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            ranges,
+        })
     }
 
     pub fn get(&self, number: u64) -> u64 {
@@ -53,93 +67,524 @@ impl Map {
             number
         }
     }
-}
 
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/05.txt").unwrap();
-    let blocks: Vec<Vec<_>> = input
-        .trim()
-        .split("\n\n")
-        .map(|b| b.split('\n').collect::<Vec<_>>())
-        .collect();
-    let seeds: Vec<u64> = parse_seeds(blocks[0][0]);
-    let maps = vec![
-        Map::new(&blocks[1]),
-        Map::new(&blocks[2]),
-        Map::new(&blocks[3]),
-        Map::new(&blocks[4]),
-        Map::new(&blocks[5]),
-        Map::new(&blocks[6]),
-        Map::new(&blocks[7]),
-    ];
+    // Map whole ranges of numbers at once by splitting them wherever they
+    // cross the boundaries of the map's ranges. Like `get`, the first range
+    // containing a number decides where it goes.
+    pub fn get_ranges(&self, input: &[std::ops::Range<u64>]) -> Vec<std::ops::Range<u64>> {
+        let mut output = vec![];
+        let mut pending: Vec<_> = input.iter().filter(|r| !r.is_empty()).cloned().collect();
+
+        for range in &self.ranges {
+            let mut unmapped = vec![];
+            for r in pending {
+                let start = r.start.max(range.src);
+                let end = r.end.min(range.src + range.rng);
+                if start >= end {
+                    unmapped.push(r);
+                    continue;
+                }
+
+                output.push(range.transform(start)..range.transform(end - 1) + 1);
+                if r.start < start {
+                    unmapped.push(r.start..start);
+                }
+                if end < r.end {
+                    unmapped.push(end..r.end);
+                }
+            }
+            pending = unmapped;
+        }
 
-    let mut locations: Vec<u64> = vec![];
-    for seed in seeds {
-        let soil = maps[0].get(seed);
-        let fertilizer = maps[1].get(soil);
-        let water = maps[2].get(fertilizer);
-        let light = maps[3].get(water);
-        let temperature = maps[4].get(light);
-        let humidity = maps[5].get(temperature);
-        let location = maps[6].get(humidity);
-        locations.push(location);
+        output.extend(pending);
+        output
     }
-    let lowest = locations.iter().min().unwrap();
 
-    println!("05 - Part One: {}", lowest);
+    // Disjoint ranges, sorted by source, covering every number below
+    // `u64::MAX`. Numbers the map leaves alone get ranges mapping to
+    // themselves.
+    fn partition(&self) -> Vec<Range> {
+        let mut pieces = vec![];
+        let mut covered: Vec<std::ops::Range<u64>> = vec![];
+
+        for range in &self.ranges {
+            let mut uncovered: Vec<_> = std::iter::once(range.src..range.src + range.rng).collect();
+            for c in &covered {
+                uncovered = uncovered
+                    .into_iter()
+                    .flat_map(|u| [u.start..u.end.min(c.start), u.start.max(c.end)..u.end])
+                    .filter(|u| !u.is_empty())
+                    .collect();
+            }
+            for u in uncovered {
+                pieces.push(Range {
+                    src: u.start,
+                    dst: range.transform(u.start),
+                    rng: u.end - u.start,
+                });
+            }
+            covered.push(range.src..range.src + range.rng);
+        }
+
+        pieces.sort_by_key(|p| p.src);
+        let mut partition = vec![];
+        let mut next = 0;
+        for piece in pieces {
+            if next < piece.src {
+                partition.push(Range {
+                    src: next,
+                    dst: next,
+                    rng: piece.src - next,
+                });
+            }
+            next = piece.src + piece.rng;
+            partition.push(piece);
+        }
+        if next < u64::MAX {
+            partition.push(Range {
+                src: next,
+                dst: next,
+                rng: u64::MAX - next,
+            });
+        }
+        partition
+    }
+
+    /// A single map doing the work of this map followed by `other`.
+    pub fn then(&self, other: &Map) -> Map {
+        let second = other.partition();
+        let mut ranges: Vec<Range> = vec![];
+
+        for first in self.partition() {
+            let start = first.dst;
+            let end = first.dst + first.rng;
+            for piece in second
+                .iter()
+                .filter(|p| p.src < end && start < p.src + p.rng)
+            {
+                let overlap_start = start.max(piece.src);
+                let overlap_end = end.min(piece.src + piece.rng);
+                let range = Range {
+                    src: first.src + (overlap_start - start),
+                    dst: piece.transform(overlap_start),
+                    rng: overlap_end - overlap_start,
+                };
+                if range.src == range.dst {
+                    continue;
+                }
+
+                // Merge with the previous range when it continues it
+                if let Some(last) = ranges.last_mut() {
+                    if last.src + last.rng == range.src && last.dst + last.rng == range.dst {
+                        last.rng += range.rng;
+                        continue;
+                    }
+                }
+                ranges.push(range);
+            }
+        }
+
+        Map {
+            from: self.from.clone(),
+            to: other.to.clone(),
+            ranges,
+        }
+    }
+
+    /// The map going from destination back to source, if every destination
+    /// comes from exactly one source.
+    pub fn inverse(&self) -> Option<Map> {
+        let mut pieces = self.partition();
+        pieces.sort_by_key(|p| p.dst);
+
+        let mut next = 0;
+        for piece in &pieces {
+            if piece.dst != next {
+                return None;
+            }
+            next = piece.dst + piece.rng;
+        }
+
+        let ranges = pieces
+            .into_iter()
+            .filter(|p| p.src != p.dst)
+            .map(|p| Range {
+                src: p.dst,
+                dst: p.src,
+                rng: p.rng,
+            })
+            .collect();
+        Some(Map {
+            from: self.to.clone(),
+            to: self.from.clone(),
+            ranges,
+        })
+    }
 }
 
-fn parse_seed_ranges(input: &str) -> Vec<std::ops::Range<u64>> {
+fn seed_ranges(seeds: &[u64]) -> Result<Vec<std::ops::Range<u64>>> {
     let mut ranges: Vec<_> = vec![];
-    let (_, number_str) = input.split_once(": ").unwrap();
-    let numbers: Vec<u64> = number_str
-        .split(' ')
-        .map(|n| n.parse::<u64>().unwrap())
-        .collect();
-    for chunk in numbers.chunks(2) {
+    if !seeds.len().is_multiple_of(2) {
+        return Err(Error::new("Expected pairs of seed numbers"));
+    }
+    for chunk in seeds.chunks(2) {
         let (src, rng) = (chunk[0], chunk[1]);
-        ranges.push(src..src + rng);
+        let end = src
+            .checked_add(rng)
+            .ok_or_else(|| Error::new(format!("Seed range {} {} is out of bounds", src, rng)))?;
+        ranges.push(src..end);
+    }
+    Ok(ranges)
+}
+
+fn parse_maps(parser: &Parser, blocks: &[Vec<&str>]) -> Result<Vec<Map>> {
+    let mut maps: Vec<Map> = vec![];
+    for block in &blocks[1..] {
+        let map = Map::parse(parser, block)?;
+        if maps.iter().any(|m| m.from == map.from && m.to == map.to) {
+            return Err(parser.error(block[0], "Duplicate map"));
+        }
+        maps.push(map);
     }
-    ranges
+    Ok(maps)
 }
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/05.txt").unwrap();
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: Vec<Map>,
+}
+
+impl Almanac {
+    /// The shortest chain of maps converting category `from` into `to`.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&Map>> {
+        let mut previous: HashMap<&str, Option<&Map>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = vec![];
+                let mut category = to;
+                while let Some(Some(map)) = previous.get(category) {
+                    path.push(*map);
+                    category = &map.from;
+                }
+                path.reverse();
+                return Ok(path);
+            }
+
+            for map in self.maps.iter().filter(|m| m.from == category) {
+                if !previous.contains_key(map.to.as_str()) {
+                    previous.insert(&map.to, Some(map));
+                    queue.push_back(&map.to);
+                }
+            }
+        }
+
+        Err(Error::new(format!("No conversion from {} to {}", from, to)))
+    }
+
+    pub fn convert(&self, from: &str, to: &str, number: u64) -> Result<u64> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(number, |number, map| map.get(number)))
+    }
+
+    /// A single map converting category `to` back into `from`.
+    pub fn inverse(&self, from: &str, to: &str) -> Result<Map> {
+        self.compose(from, to)?.inverse().ok_or_else(|| {
+            Error::new(format!(
+                "Conversion from {} to {} is not invertible",
+                from, to
+            ))
+        })
+    }
+
+    /// Compose every map between `from` and `to` into a single map.
+    pub fn compose(&self, from: &str, to: &str) -> Result<Map> {
+        let identity = Map {
+            from: from.to_string(),
+            to: from.to_string(),
+            ranges: vec![],
+        };
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(identity, |composed, map| composed.then(map)))
+    }
+}
+
+fn parse_almanac(input: &str) -> Result<Almanac> {
+    let parser = Parser::new(input);
     let blocks: Vec<Vec<_>> = input
         .trim()
         .split("\n\n")
         .map(|b| b.split('\n').collect::<Vec<_>>())
         .collect();
-    let seeds = parse_seed_ranges(blocks[0][0]);
-    let maps = vec![
-        Map::new(&blocks[1]),
-        Map::new(&blocks[2]),
-        Map::new(&blocks[3]),
-        Map::new(&blocks[4]),
-        Map::new(&blocks[5]),
-        Map::new(&blocks[6]),
-        Map::new(&blocks[7]),
-    ];
+    let seeds = parse_seeds(&parser, blocks[0][0])?;
+    let maps = parse_maps(&parser, &blocks)?;
+    Ok(Almanac { seeds, maps })
+}
 
-    let mut min = std::u64::MAX;
+fn lowest_location(almanac: &Almanac) -> Result<Option<u64>> {
+    let seeds = seed_ranges(&almanac.seeds)?;
+    let locations = almanac.compose("seed", "location")?.get_ranges(&seeds);
+    Ok(locations.iter().map(|r| r.start).min())
+}
+
+// Walk the locations upward from 0, a range of the inverted map at a time,
+// until one of them leads back to a seed.
+fn lowest_location_reverse(almanac: &Almanac) -> Result<Option<u64>> {
+    let seeds = seed_ranges(&almanac.seeds)?;
+    let location_to_seed = almanac.inverse("seed", "location")?;
+
+    for piece in location_to_seed.partition() {
+        let lowest = seeds
+            .iter()
+            .map(|s| s.start.max(piece.dst)..s.end.min(piece.dst + piece.rng))
+            .filter(|s| !s.is_empty())
+            .map(|s| piece.src + (s.start - piece.dst))
+            .min();
+        if lowest.is_some() {
+            return Ok(lowest);
+        }
+    }
+
+    Ok(None)
+}
+
+// Map every seed one by one. Far too slow for the puzzle input, but useful to
+// check `lowest_location` against.
+#[cfg(test)]
+fn lowest_location_brute_force(almanac: &Almanac) -> Result<Option<u64>> {
+    use rayon::prelude::*;
+
+    let maps = almanac.path("seed", "location")?;
+    let seeds = seed_ranges(&almanac.seeds)?;
+
+    let mut min = None;
     for range in seeds {
-        let lowest = range
+        let Some(lowest) = range
             .into_par_iter()
-            .map(|seed| {
-                let soil = maps[0].get(seed);
-                let fertilizer = maps[1].get(soil);
-                let water = maps[2].get(fertilizer);
-                let light = maps[3].get(water);
-                let temperature = maps[4].get(light);
-                let humidity = maps[5].get(temperature);
-                maps[6].get(humidity) // Location
-            })
+            .map(|seed| maps.iter().fold(seed, |number, map| map.get(number)))
             .min()
-            .unwrap();
-        if lowest < min {
-            min = lowest;
+        else {
+            continue;
+        };
+        if min.is_none_or(|m| lowest < m) {
+            min = Some(lowest);
         }
     }
 
-    println!("05 - Part Two: {}", min);
+    Ok(min)
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input<'a> = Almanac;
+    type View<'a> = Almanac;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        parse_almanac(input)
+    }
+
+    fn part_one(&self, almanac: &Almanac) -> Result<Answer> {
+        let mut locations: Vec<u64> = vec![];
+        for &seed in &almanac.seeds {
+            locations.push(almanac.convert("seed", "location", seed)?);
+        }
+        let lowest = *locations
+            .iter()
+            .min()
+            .ok_or_else(|| Error::new("No seeds"))?;
+
+        Ok(lowest.into())
+    }
+
+    fn part_two(&self, almanac: &Almanac) -> Result<Answer> {
+        let min = lowest_location(almanac)?;
+
+        // Check the answer from the other direction in debug builds, when the
+        // maps can be inverted
+        if cfg!(debug_assertions) {
+            if let Ok(reverse) = lowest_location_reverse(almanac) {
+                assert_eq!(
+                    min, reverse,
+                    "Lowest location disagrees with the reverse search"
+                );
+            }
+        }
+
+        let min = min.ok_or_else(|| Error::new("No seeds"))?;
+        Ok(min.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATEGORIES: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+
+    // A small linear congruential generator, enough to get varied maps
+    // without pulling in a dependency.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: u64) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) % bound
+        }
+    }
+
+    fn random_almanac(lcg: &mut Lcg) -> Almanac {
+        let seeds = (0..4).flat_map(|_| [lcg.next(100), lcg.next(30)]).collect();
+        let maps = CATEGORIES
+            .windows(2)
+            .map(|pair| Map {
+                from: pair[0].to_string(),
+                to: pair[1].to_string(),
+                ranges: (0..lcg.next(5))
+                    .map(|_| Range {
+                        src: lcg.next(120),
+                        dst: lcg.next(120),
+                        rng: lcg.next(40),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Almanac { seeds, maps }
+    }
+
+    fn example() -> Almanac {
+        let input = std::fs::read_to_string("input/05_test.txt").unwrap();
+        parse_almanac(&input).unwrap()
+    }
+
+    #[test]
+    fn test_get_ranges() {
+        let map = Map {
+            from: String::from("a"),
+            to: String::from("b"),
+            ranges: vec![
+                Range {
+                    src: 10,
+                    dst: 100,
+                    rng: 5,
+                },
+                Range {
+                    src: 12,
+                    dst: 200,
+                    rng: 10,
+                },
+            ],
+        };
+        let mut ranges = map.get_ranges(&[0..10, 10..30]);
+        ranges.sort_by_key(|r| r.start);
+        assert_eq!(ranges, vec![0..10, 22..30, 100..105, 203..210]);
+    }
+
+    #[test]
+    fn test_lowest_location_matches_brute_force() {
+        let almanac = example();
+        assert_eq!(lowest_location(&almanac), Ok(Some(46)));
+        assert_eq!(lowest_location_brute_force(&almanac), Ok(Some(46)));
+
+        let mut lcg = Lcg(2023);
+        for _ in 0..200 {
+            let almanac = random_almanac(&mut lcg);
+            assert_eq!(
+                lowest_location(&almanac),
+                lowest_location_brute_force(&almanac)
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        let almanac = example();
+        // Seed 79, soil 81, fertilizer 81, water 81, light 74, temperature 78,
+        // humidity 78, location 82
+        assert_eq!(almanac.convert("seed", "location", 79), Ok(82));
+        assert_eq!(almanac.convert("water", "humidity", 81), Ok(78));
+        assert_eq!(almanac.convert("soil", "soil", 5), Ok(5));
+        assert!(almanac.convert("location", "seed", 82).is_err());
+        assert!(almanac.convert("seed", "weather", 79).is_err());
+    }
+
+    #[test]
+    fn test_compose() {
+        let mut lcg = Lcg(25);
+        let mut almanacs: Vec<_> = (0..20).map(|_| random_almanac(&mut lcg)).collect();
+        almanacs.push(example());
+
+        for almanac in almanacs {
+            for (i, from) in CATEGORIES.iter().enumerate() {
+                for to in &CATEGORIES[i..] {
+                    let composed = almanac.compose(from, to).unwrap();
+                    for number in 0..170 {
+                        assert_eq!(Ok(composed.get(number)), almanac.convert(from, to, number));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        let almanac = example();
+        let location_to_seed = almanac.inverse("seed", "location").unwrap();
+        for (seed, location) in [(79, 82), (14, 43), (55, 86), (13, 35)] {
+            assert_eq!(location_to_seed.get(location), seed);
+        }
+
+        let seed_to_location = almanac.compose("seed", "location").unwrap();
+        for location in 0..200 {
+            assert_eq!(
+                seed_to_location.get(location_to_seed.get(location)),
+                location
+            );
+        }
+
+        // Both 0 and 5 map to 5, and nothing maps to 0
+        let map = Map {
+            from: String::from("a"),
+            to: String::from("b"),
+            ranges: vec![Range {
+                src: 0,
+                dst: 5,
+                rng: 1,
+            }],
+        };
+        assert_eq!(map.inverse(), None);
+    }
+
+    #[test]
+    fn test_lowest_location_reverse() {
+        assert_eq!(lowest_location_reverse(&example()), Ok(Some(46)));
+
+        let mut lcg = Lcg(9);
+        let mut checked = 0;
+        for _ in 0..200 {
+            let almanac = random_almanac(&mut lcg);
+            if let Ok(reverse) = lowest_location_reverse(&almanac) {
+                assert_eq!(reverse, lowest_location(&almanac).unwrap());
+                checked += 1;
+            }
+        }
+        assert!(checked > 0);
+    }
 }
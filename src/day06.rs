@@ -1,5 +1,9 @@
+use crate::error::{Error, Result};
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
 use regex::Regex;
 use std::iter::zip;
+use std::ops::RangeInclusive;
 
 #[derive(Debug)]
 struct Race {
@@ -8,56 +12,236 @@ struct Race {
 }
 
 impl Race {
+    fn travelled(&self, hold: u64) -> u128 {
+        hold as u128 * (self.time - hold) as u128
+    }
+
+    fn wins(&self, hold: u64) -> bool {
+        self.travelled(hold) > self.distance as u128
+    }
+
+    // Holding the button for h milliseconds travels h * (time - h), so the
+    // winning holds lie strictly between the roots of
+    // h^2 - time * h + distance = 0. The integer square root gets within one
+    // of the lower root, and the upper bound follows by symmetry.
+    pub fn winning_holds(&self) -> Option<RangeInclusive<u64>> {
+        let time = self.time as u128;
+        let discriminant = (time * time).checked_sub(4 * self.distance as u128)?;
+        let root = discriminant.isqrt();
+
+        let mut low = ((time - root.min(time)) / 2) as u64;
+        while low > 0 && self.wins(low - 1) {
+            low -= 1;
+        }
+        while low <= self.time / 2 && !self.wins(low) {
+            low += 1;
+        }
+
+        if low <= self.time / 2 {
+            Some(low..=self.time - low)
+        } else {
+            None
+        }
+    }
+
     pub fn ways_to_win(&self) -> u64 {
+        self.winning_holds()
+            .map_or(0, |holds| holds.end() - holds.start() + 1)
+    }
+}
+
+fn parse_lines(input: &str) -> Result<(&str, &str)> {
+    let mut lines = input.lines();
+    match (lines.next(), lines.next()) {
+        (Some(time), Some(distance)) => Ok((time, distance)),
+        _ => Err(Error::new(
+            "Expected a line of times and a line of distances",
+        )),
+    }
+}
+
+/// The races as listed, with the digits of each number kept for reading the
+/// sheet as a single race by ignoring the spaces between numbers.
+pub struct Sheet<'a> {
+    parser: Parser<'a>,
+    races: Vec<Race>,
+    time_digits: Vec<&'a str>,
+    distance_digits: Vec<&'a str>,
+}
+
+impl Sheet<'_> {
+    // Read numbers as one by joining their digits, pointing at the number
+    // that makes the result too large.
+    fn join(&self, numbers: &[&str]) -> Result<u64> {
+        let mut joined: u64 = 0;
+        for &number in numbers {
+            joined = number
+                .bytes()
+                .try_fold(joined, |n, digit| {
+                    n.checked_mul(10)?.checked_add((digit - b'0') as u64)
+                })
+                .ok_or_else(|| self.parser.error(number, "Joined number is too large"))?;
+        }
+        Ok(joined)
+    }
+
+    /// The single race read by ignoring the spaces between numbers.
+    fn race(&self) -> Result<Race> {
+        Ok(Race {
+            time: self.join(&self.time_digits)?,
+            distance: self.join(&self.distance_digits)?,
+        })
+    }
+}
+
+fn parse_sheet(input: &str) -> Result<Sheet<'_>> {
+    let parser = Parser::new(input);
+    let (time_line, distance_line) = parse_lines(input)?;
+    let digits_re = Regex::new(r"(\d+)").unwrap();
+
+    let time_digits: Vec<&str> = digits_re.find_iter(time_line).map(|d| d.as_str()).collect();
+    let distance_digits: Vec<&str> = digits_re
+        .find_iter(distance_line)
+        .map(|d| d.as_str())
+        .collect();
+    if time_digits.is_empty() {
+        return Err(parser.error(time_line, "Expected a time"));
+    }
+    if time_digits.len() != distance_digits.len() {
+        return Err(parser.error(distance_line, "Expected a distance for every time"));
+    }
+
+    let mut races = vec![];
+    for (time, distance) in zip(&time_digits, &distance_digits) {
+        races.push(Race {
+            time: parser.parse(time)?,
+            distance: parser.parse(distance)?,
+        });
+    }
+
+    Ok(Sheet {
+        parser,
+        races,
+        time_digits,
+        distance_digits,
+    })
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input<'a> = Sheet<'a>;
+    type View<'a> = Sheet<'a>;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        parse_sheet(input)
+    }
+
+    fn part_one(&self, sheet: &Sheet) -> Result<Answer> {
+        let total = sheet
+            .races
+            .iter()
+            .try_fold(1u64, |total, race| total.checked_mul(race.ways_to_win()))
+            .ok_or_else(|| Error::new("Product of the ways to win is too large"))?;
+        Ok(total.into())
+    }
+
+    fn part_two(&self, sheet: &Sheet) -> Result<Answer> {
+        Ok(sheet.race()?.ways_to_win().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ways_to_win_brute_force(race: &Race) -> u64 {
         let mut wins = 0;
-        for n in 0..self.time {
+        for n in 0..race.time {
             let speed = n;
-            let remaining = self.time - n;
-            if speed * remaining > self.distance {
+            let remaining = race.time - n;
+            if speed * remaining > race.distance {
                 wins += 1;
             }
         }
         wins
     }
-}
 
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/06.txt").unwrap();
-    let digits_re = Regex::new(r"(\d+)").unwrap();
-    let times: Vec<u64> = digits_re
-        .find_iter(input.lines().next().unwrap())
-        .map(|d| d.as_str().parse::<u64>().unwrap())
-        .collect();
-    let distances: Vec<u64> = digits_re
-        .find_iter(input.lines().nth(1).unwrap())
-        .map(|d| d.as_str().parse::<u64>().unwrap())
-        .collect();
-    let races: Vec<Race> = zip(times, distances)
-        .map(|(time, distance)| Race { time, distance })
-        .collect();
+    #[test]
+    fn test_ways_to_win_matches_brute_force() {
+        for time in 0..100 {
+            for distance in 0..(time * time / 4 + 3) {
+                let race = Race { time, distance };
+                assert_eq!(
+                    race.ways_to_win(),
+                    ways_to_win_brute_force(&race),
+                    "{:?}",
+                    race
+                );
+            }
+        }
+    }
 
-    let ways_to_win: Vec<u64> = races.iter().map(|race| race.ways_to_win()).collect();
-    let total: u64 = ways_to_win.iter().product();
-    println!("06 - Part One: {}", total);
-}
+    #[test]
+    fn test_winning_holds_at_the_limits() {
+        let races = [
+            Race {
+                time: u64::MAX,
+                distance: u64::MAX,
+            },
+            Race {
+                time: u64::MAX,
+                distance: 0,
+            },
+            Race {
+                time: 1 << 33,
+                distance: 1 << 62,
+            },
+            Race {
+                time: 71530,
+                distance: 940200,
+            },
+            Race {
+                time: 4_000_000_000,
+                distance: 3_999_999_999_999_999_999,
+            },
+        ];
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/06.txt").unwrap();
-    let digits_re = Regex::new(r"(\d+)").unwrap();
-    let time: u64 = digits_re
-        .find_iter(input.lines().next().unwrap())
-        .map(|d| d.as_str().to_string())
-        .reduce(|cur: String, next: String| cur + &next)
-        .unwrap()
-        .parse::<u64>()
-        .unwrap();
-    let distance: u64 = digits_re
-        .find_iter(input.lines().nth(1).unwrap())
-        .map(|d| d.as_str().to_string())
-        .reduce(|cur: String, next: String| cur + &next)
-        .unwrap()
-        .parse::<u64>()
-        .unwrap();
-    let race = Race { time, distance };
-    println!("06 - Part Two: {}", race.ways_to_win());
+        for race in races {
+            let Some(holds) = race.winning_holds() else {
+                // Even the best hold, half the time, must not win
+                assert!(!race.wins(race.time / 2), "{:?}", race);
+                continue;
+            };
+            let (low, high) = (*holds.start(), *holds.end());
+            assert!(race.wins(low) && race.wins(high), "{:?}", race);
+            assert!(!race.wins(low - 1) && !race.wins(high + 1), "{:?}", race);
+        }
+
+        let race = Race {
+            time: 71530,
+            distance: 940200,
+        };
+        assert_eq!(race.ways_to_win(), 71503);
+    }
+
+    #[test]
+    fn test_joined_race_too_large() {
+        let input = "Time: 4000000000 4000000000\nDistance: 0 0\n";
+        let sheet = Day06.parse(input).unwrap();
+        let answer = Day06.part_one(&sheet).unwrap();
+        assert_eq!(answer.to_string(), "15999999992000000001");
+
+        let error = Day06.part_two(&sheet).unwrap_err();
+        assert_eq!(error.message, "Joined number is too large");
+        assert_eq!((error.line, error.column), (Some(1), Some(18)));
+    }
+
+    #[test]
+    fn test_product_too_large() {
+        let input = "Time: 1099511627776 1099511627776\nDistance: 0 0\n";
+        let sheet = Day06.parse(input).unwrap();
+        let error = Day06.part_one(&sheet).unwrap_err();
+        assert_eq!(error.message, "Product of the ways to win is too large");
+    }
 }
@@ -1,10 +1,11 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
+use crate::error::{Error, Result};
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
 use std::fmt;
-use std::mem;
+use std::iter::zip;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Rank {
+pub enum Rank {
     Joker = 1,
     Two,
     Three,
@@ -21,17 +22,24 @@ enum Rank {
     Ace,
 }
 
-#[derive(Clone, Debug, Eq)]
-struct Card {
-    rank: Rank,
-}
-
-impl Card {
-    pub fn new(input: char) -> Self {
-        Self::new_with_joker(input, false)
-    }
-
-    pub fn new_with_joker(input: char, jokers: bool) -> Self {
+impl Rank {
+    const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    fn parse(input: char) -> Option<Self> {
         let rank = match input {
             '2' => Rank::Two,
             '3' => Rank::Three,
@@ -46,31 +54,117 @@ impl Card {
             'Q' => Rank::Queen,
             'K' => Rank::King,
             'A' => Rank::Ace,
-            _ => panic!("Unknown rank"),
+            _ => return None,
         };
-        if jokers && rank == Rank::Jack {
-            Card { rank: Rank::Joker }
-        } else {
-            Card { rank }
-        }
+        Some(rank)
     }
 }
 
-impl Ord for Card {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.rank.cmp(&other.rank)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    fn parse(input: char) -> Option<Self> {
+        match input {
+            'C' | '♣' => Some(Suit::Clubs),
+            'D' | '♦' => Some(Suit::Diamonds),
+            'H' | '♥' => Some(Suit::Hearts),
+            'S' | '♠' => Some(Suit::Spades),
+            _ => None,
+        }
     }
 }
 
-impl PartialOrd for Card {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+/// How hands of the same category are ordered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Compare the cards one by one in the order they were dealt, as in Camel
+    /// Cards.
+    CardOrder,
+    /// Compare the ranks making up the win, then the remaining cards from the
+    /// highest, as in poker.
+    Kickers,
+}
+
+/// The rules hands are played by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    /// Ranks played as wildcards, standing in for whichever card makes the
+    /// best hand. Wildcards count as the lowest card when breaking ties.
+    pub wild: Vec<Rank>,
+    /// Whether every card is written as its rank followed by its suit, such
+    /// as `AS` or `T♥`.
+    pub suits: bool,
+    /// Whether five consecutive ranks make a straight. Aces may also be low.
+    pub straights: bool,
+    /// Whether five cards of the same suit make a flush.
+    pub flushes: bool,
+    pub tie_break: TieBreak,
+}
+
+impl Rules {
+    pub fn camel_cards() -> Self {
+        Rules {
+            wild: vec![],
+            suits: false,
+            straights: false,
+            flushes: false,
+            tie_break: TieBreak::CardOrder,
+        }
     }
+
+    /// Camel Cards with the jacks played as jokers.
+    pub fn camel_cards_with_jokers() -> Self {
+        Rules {
+            wild: vec![Rank::Jack],
+            ..Self::camel_cards()
+        }
+    }
+
+    /// Five card poker, written with suits.
+    pub fn poker() -> Self {
+        Rules {
+            wild: vec![],
+            suits: true,
+            straights: true,
+            flushes: true,
+            tie_break: TieBreak::Kickers,
+        }
+    }
+}
+
+/// A card, ordered by rank and then by suit so that cards only compare equal
+/// when they are the same card.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Card {
+    rank: Rank,
+    suit: Option<Suit>,
 }
 
-impl PartialEq for Card {
-    fn eq(&self, other: &Self) -> bool {
-        self.rank == other.rank
+impl Card {
+    #[cfg(test)]
+    pub fn new(input: char) -> Option<Self> {
+        Some(Card {
+            rank: Rank::parse(input)?,
+            suit: None,
+        })
+    }
+
+    /// The card as played under `rules`, where wildcards become jokers.
+    fn played(&self, rules: &Rules) -> Self {
+        if rules.wild.contains(&self.rank) {
+            Card {
+                rank: Rank::Joker,
+                suit: self.suit,
+            }
+        } else {
+            *self
+        }
     }
 }
 
@@ -92,114 +186,294 @@ impl fmt::Display for Card {
             Rank::King => 'K',
             Rank::Ace => 'A',
         };
-        write!(f, "{}", rank)
+        write!(f, "{}", rank)?;
+        match self.suit {
+            Some(Suit::Clubs) => write!(f, "C"),
+            Some(Suit::Diamonds) => write!(f, "D"),
+            Some(Suit::Hearts) => write!(f, "H"),
+            Some(Suit::Spades) => write!(f, "S"),
+            None => Ok(()),
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum Win {
+/// The categories of win from the weakest to the strongest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Win {
     HighCard(Rank),
     OnePair(Rank),
     TwoPair(Rank, Rank),
     ThreeOfAKind(Rank),
+    /// Five consecutive ranks, with the highest one
+    Straight(Rank),
+    /// Five cards of one suit, with the highest rank
+    Flush(Rank),
     FullHouse(Rank, Rank),
     FourOfAKind(Rank),
+    StraightFlush(Rank),
     FiveOfAKind(Rank),
 }
 
-#[derive(Eq)]
-struct Hand {
+impl Win {
+    pub fn category(&self) -> Category {
+        match self {
+            Win::HighCard(_) => Category::HighCard,
+            Win::OnePair(_) => Category::OnePair,
+            Win::TwoPair(_, _) => Category::TwoPair,
+            Win::ThreeOfAKind(_) => Category::ThreeOfAKind,
+            Win::Straight(_) => Category::Straight,
+            Win::Flush(_) => Category::Flush,
+            Win::FullHouse(_, _) => Category::FullHouse,
+            Win::FourOfAKind(_) => Category::FourOfAKind,
+            Win::StraightFlush(_) => Category::StraightFlush,
+            Win::FiveOfAKind(_) => Category::FiveOfAKind,
+        }
+    }
+
+    fn ranks(&self) -> Vec<Rank> {
+        match *self {
+            Win::TwoPair(high, low) | Win::FullHouse(high, low) => vec![high, low],
+            Win::HighCard(rank)
+            | Win::OnePair(rank)
+            | Win::ThreeOfAKind(rank)
+            | Win::Straight(rank)
+            | Win::Flush(rank)
+            | Win::FourOfAKind(rank)
+            | Win::StraightFlush(rank)
+            | Win::FiveOfAKind(rank) => vec![rank],
+        }
+    }
+}
+
+// The highest card of the best straight the ranks can be part of, with the
+// jokers filling the gaps.
+fn straight(ranks: &[Rank], jokers: usize) -> Option<Rank> {
+    if ranks.len() + jokers != 5 {
+        return None;
+    }
+
+    // Aces also count low, below the twos
+    let windows = Rank::ALL
+        .windows(5)
+        .map(<[Rank]>::to_vec)
+        .rev()
+        .chain([vec![
+            Rank::Ace,
+            Rank::Two,
+            Rank::Three,
+            Rank::Four,
+            Rank::Five,
+        ]]);
+    for window in windows {
+        let mut needed = window.clone();
+        let all_fit = ranks.iter().all(|rank| {
+            let found = needed.iter().position(|r| r == rank);
+            found.map(|i| needed.swap_remove(i)).is_some()
+        });
+        if all_fit {
+            return Some(window[4]);
+        }
+    }
+
+    None
+}
+
+// The ranks of a flush from the highest, with each joker standing in for the
+// highest rank missing from the suit.
+fn flush_ranks(cards: &[Card]) -> Vec<Rank> {
+    let mut ranks: Vec<_> = cards
+        .iter()
+        .map(|c| c.rank)
+        .filter(|&rank| rank != Rank::Joker)
+        .collect();
+    let jokers = cards.len() - ranks.len();
+    let missing: Vec<_> = Rank::ALL
+        .iter()
+        .rev()
+        .filter(|rank| !ranks.contains(rank))
+        .take(jokers)
+        .copied()
+        .collect();
+    ranks.extend(missing);
+    ranks.sort_by(|a, b| b.cmp(a));
+    ranks
+}
+
+/// How strong a hand is under the rules it was played by: its category of win,
+/// then the ranks compared between hands of the same category.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Strength {
+    category: Category,
+    tie_break: Vec<Rank>,
+}
+
+/// Hands are ordered by strength, and hands of equal strength by their cards
+/// and then their bid, so that only identical hands compare equal.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hand {
+    strength: Strength,
     cards: Vec<Card>,
-    win: Win,
     bid: u32,
+    win: Win,
 }
 
 impl Hand {
-    pub fn win(cards: &Vec<Card>) -> Win {
-        let mut bins: HashMap<Rank, u8> = HashMap::new();
+    /// The best win the cards make under `rules`, with any wildcards already
+    /// played as jokers.
+    pub fn evaluate(cards: &[Card], rules: &Rules) -> Win {
+        let mut counts = [0u8; Rank::Ace as usize + 1];
         for card in cards {
-            if let Some(number) = bins.get_mut(&card.rank) {
-                *number += 1;
-            } else {
-                bins.insert(card.rank, 1);
-            }
+            counts[card.rank as usize] += 1;
         }
 
-        // Add the jokers to the largest, highest ranking bin
-        let number_of_jokers = bins.get(&Rank::Joker).unwrap_or(&0);
-        let (rank, highest) = bins
+        // The ranks in the hand, the most common and then the highest first
+        let mut bins: Vec<(u8, Rank)> = Rank::ALL
             .iter()
-            .filter(|(r, _)| **r != Rank::Joker)
-            .max_by_key(|bin| (bin.1, bin.0))
-            .unwrap_or((&Rank::Joker, number_of_jokers));
-        let mut num = *highest;
-        if rank != &Rank::Joker {
-            num += number_of_jokers;
+            .filter(|&&rank| counts[rank as usize] > 0)
+            .map(|&rank| (counts[rank as usize], rank))
+            .collect();
+        bins.sort_by(|a, b| b.cmp(a));
+
+        // Add the jokers to the largest, highest ranking bin
+        let jokers = counts[Rank::Joker as usize];
+        let (highest, rank) = bins.first().copied().unwrap_or((0, Rank::Joker));
+        let of_a_kind = match (highest + jokers, bins.get(1)) {
+            (5, _) => Win::FiveOfAKind(rank),
+            (4, _) => Win::FourOfAKind(rank),
+            (3, Some(&(2, other))) => Win::FullHouse(rank, other),
+            (3, _) => Win::ThreeOfAKind(rank),
+            (2, Some(&(2, other))) => Win::TwoPair(rank, other),
+            (2, _) => Win::OnePair(rank),
+            _ => Win::HighCard(rank),
+        };
+
+        if !rules.straights && !rules.flushes {
+            return of_a_kind;
         }
 
-        match num {
-            5 => Win::FiveOfAKind(*rank),
-            4 => Win::FourOfAKind(*rank),
-            3 => {
-                if let Some((other_rank, _)) = bins
-                    .iter()
-                    .filter(|(r, _)| *r != rank && **r != Rank::Joker)
-                    .find(|(_, n)| **n == 2)
-                {
-                    Win::FullHouse(*rank, *other_rank)
-                } else {
-                    Win::ThreeOfAKind(*rank)
-                }
-            }
-            2 => {
-                let pairs: Vec<_> = bins.iter().filter(|(_, n)| **n == 2).collect();
-                if pairs.len() == 2 {
-                    let mut ranks: Vec<_> = pairs.iter().map(|(r, _)| r).collect();
-                    ranks.sort();
-                    Win::TwoPair(**ranks[1], **ranks[0])
-                } else {
-                    Win::OnePair(*rank)
-                }
-            }
-            _ => Win::HighCard(*rank),
+        let real: Vec<_> = cards.iter().filter(|c| c.rank != Rank::Joker).collect();
+        let ranks: Vec<_> = real.iter().map(|c| c.rank).collect();
+
+        let straight = match rules.straights {
+            true => straight(&ranks, jokers as usize),
+            false => None,
+        };
+        let flush = rules.flushes
+            && cards.len() == 5
+            && real
+                .iter()
+                .all(|c| c.suit.is_some() && c.suit == real[0].suit);
+
+        let mut wins = vec![of_a_kind];
+        match (straight, flush) {
+            (Some(high), true) => wins.push(Win::StraightFlush(high)),
+            (Some(high), false) => wins.push(Win::Straight(high)),
+            (None, true) => wins.push(Win::Flush(flush_ranks(cards)[0])),
+            (None, false) => (),
         }
+        wins.into_iter().max().unwrap()
+    }
+
+    // The ranks making up the win, then the remaining cards from the highest.
+    // Jokers always go towards the win, so they are never kickers, and every
+    // card of a straight is part of it.
+    fn kickers(cards: &[Card], win: &Win) -> Vec<Rank> {
+        let mut ranks = win.ranks();
+        match win.category() {
+            Category::Flush => return flush_ranks(cards),
+            Category::Straight | Category::StraightFlush => return ranks,
+            _ => (),
+        }
+        let mut kickers: Vec<_> = cards
+            .iter()
+            .map(|c| c.rank)
+            .filter(|r| *r != Rank::Joker && !ranks.contains(r))
+            .collect();
+        kickers.sort_by(|a, b| b.cmp(a));
+        ranks.extend(kickers);
+        ranks
     }
 
-    pub fn new(input: &str) -> Self {
+    #[cfg(test)]
+    pub fn new(input: &str) -> Result<Self> {
         Self::new_with_joker(input, false)
     }
 
-    pub fn new_with_joker(input: &str, joker: bool) -> Self {
-        let (cards_str, bid_str) = input.split_once(' ').unwrap();
-        let cards = cards_str
-            .chars()
-            .map(|c| Card::new_with_joker(c, joker))
-            .collect();
-        let win = Hand::win(&cards);
-        let bid = bid_str.parse::<u32>().unwrap();
-        Hand { cards, win, bid }
+    #[cfg(test)]
+    pub fn new_with_joker(input: &str, joker: bool) -> Result<Self> {
+        let rules = match joker {
+            true => Rules::camel_cards_with_jokers(),
+            false => Rules::camel_cards(),
+        };
+        Self::parse(&Parser::new(input), input, &rules)
     }
-}
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        if mem::discriminant(&self.win) == mem::discriminant(&other.win) {
-            self.cards.cmp(&other.cards)
-        } else {
-            self.win.cmp(&other.win)
+    pub fn parse(parser: &Parser, input: &str, rules: &Rules) -> Result<Self> {
+        let (cards_str, bid_str) = parser.split_once(input, " ")?;
+        let mut cards = vec![];
+        let mut chars = cards_str.char_indices();
+        while let Some((i, c)) = chars.next() {
+            let rank = Rank::parse(c)
+                .ok_or_else(|| parser.error(&cards_str[i..i + c.len_utf8()], "Unknown rank"))?;
+            let suit = if rules.suits {
+                let (i, c) = chars
+                    .next()
+                    .ok_or_else(|| parser.error(&cards_str[i..], "Expected a suit"))?;
+                let suit = Suit::parse(c)
+                    .ok_or_else(|| parser.error(&cards_str[i..i + c.len_utf8()], "Unknown suit"))?;
+                Some(suit)
+            } else {
+                None
+            };
+            cards.push(Card { rank, suit }.played(rules));
         }
+        if cards.len() != 5 {
+            return Err(parser.error(cards_str, "Expected five cards"));
+        }
+        let bid = parser.parse::<u32>(bid_str)?;
+        Ok(Self::new_played(cards, rules, bid))
     }
-}
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    fn new_played(cards: Vec<Card>, rules: &Rules, bid: u32) -> Self {
+        let win = Hand::evaluate(&cards, rules);
+        let strength = Strength {
+            category: win.category(),
+            tie_break: match rules.tie_break {
+                TieBreak::CardOrder => cards.iter().map(|c| c.rank).collect(),
+                TieBreak::Kickers => Hand::kickers(&cards, &win),
+            },
+        };
+        Hand {
+            strength,
+            cards,
+            bid,
+            win,
+        }
     }
-}
 
-impl PartialEq for Hand {
-    fn eq(&self, other: &Self) -> bool {
-        self.win == other.win
+    /// The same hand played by other rules.
+    pub fn with_rules(&self, rules: &Rules) -> Self {
+        let cards = self.cards.iter().map(|c| c.played(rules)).collect();
+        Self::new_played(cards, rules, self.bid)
+    }
+
+    /// The same hand with every jack played as a joker.
+    pub fn with_jokers(&self) -> Self {
+        self.with_rules(&Rules::camel_cards_with_jokers())
     }
 }
 
@@ -210,42 +484,64 @@ impl fmt::Debug for Hand {
     }
 }
 
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/07.txt").unwrap();
-    let mut hands: Vec<Hand> = input.lines().map(Hand::new).collect();
-    hands.sort();
-
-    let mut total = 0;
-    for (index, hand) in hands.iter().enumerate() {
-        total += (index as u32 + 1) * hand.bid;
-    }
-    println!("07 - Part One: {}", total);
+/// Parse the ranks written in `input`, such as `J` or `2J`.
+pub fn parse_ranks(input: &str) -> Result<Vec<Rank>> {
+    input
+        .chars()
+        .map(|c| Rank::parse(c).ok_or_else(|| Error::new(format!("Unknown rank: {}", c))))
+        .collect()
 }
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/07.txt").unwrap();
-    let mut hands: Vec<Hand> = input
+/// Parse one hand per line, played under `rules`.
+pub fn parse_hands(input: &str, rules: &Rules) -> Result<Vec<Hand>> {
+    let parser = Parser::new(input);
+    input
         .lines()
-        .map(|l| Hand::new_with_joker(l, true))
-        .collect();
-    hands.sort();
+        .map(|l| Hand::parse(&parser, l, rules))
+        .collect()
+}
+
+/// The bid of every hand times its rank, from 1 for the weakest hand.
+pub fn winnings(hands: &[Hand]) -> u64 {
+    let mut ranked: Vec<&Hand> = hands.iter().collect();
+    ranked.sort();
+    zip(1.., ranked)
+        .map(|(rank, hand)| rank * hand.bid as u64)
+        .sum()
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Input<'a> = Vec<Hand>;
+    type View<'a> = [Hand];
 
-    let mut total = 0;
-    for (index, hand) in hands.iter().enumerate() {
-        total += (index as u32 + 1) * hand.bid;
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        parse_hands(input, &Rules::camel_cards())
+    }
+
+    fn part_one(&self, hands: &[Hand]) -> Result<Answer> {
+        Ok(winnings(hands).into())
+    }
+
+    fn part_two(&self, hands: &[Hand]) -> Result<Answer> {
+        let hands: Vec<Hand> = hands.iter().map(Hand::with_jokers).collect();
+        Ok(winnings(&hands).into())
     }
-    println!("07 - Part Two: {}", total);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cmp::Ordering;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
 
     #[test]
     fn test_card_compares() {
-        let c1 = Card::new('A');
-        let c2 = Card::new('A');
-        let c3 = Card::new('8');
+        let c1 = Card::new('A').unwrap();
+        let c2 = Card::new('A').unwrap();
+        let c3 = Card::new('8').unwrap();
 
         assert!(c1 == c2);
         assert!(c1 > c3);
@@ -253,42 +549,42 @@ mod tests {
 
     #[test]
     fn test_hand_compares() {
-        let hand1 = Hand::new("KK677 28");
-        let hand2 = Hand::new("KTJJT 220");
+        let hand1 = Hand::new("KK677 28").unwrap();
+        let hand2 = Hand::new("KTJJT 220").unwrap();
         assert!(hand1 > hand2);
 
-        let hand3 = Hand::new("T55J5 684");
-        let hand4 = Hand::new("QQQJA 483");
+        let hand3 = Hand::new("T55J5 684").unwrap();
+        let hand4 = Hand::new("QQQJA 483").unwrap();
         assert!(hand4 > hand3);
     }
 
     #[test]
     fn test_wins() {
-        let five = Hand::new("22222 123");
+        let five = Hand::new("22222 123").unwrap();
         assert_eq!(five.win, Win::FiveOfAKind(Rank::Two));
 
-        let full_house = Hand::new("22233 123");
+        let full_house = Hand::new("22233 123").unwrap();
         assert_eq!(full_house.win, Win::FullHouse(Rank::Two, Rank::Three));
 
-        let two_pairs = Hand::new("33577 123");
+        let two_pairs = Hand::new("33577 123").unwrap();
         assert!(two_pairs.win == Win::TwoPair(Rank::Seven, Rank::Three));
 
-        let one_pair = Hand::new("AA234 123");
+        let one_pair = Hand::new("AA234 123").unwrap();
         assert_eq!(one_pair.win, Win::OnePair(Rank::Ace));
 
-        let high_card = Hand::new("A2345 123");
+        let high_card = Hand::new("A2345 123").unwrap();
         assert_eq!(high_card.win, Win::HighCard(Rank::Ace));
     }
 
     #[test]
     fn test_joker_wins() {
-        let hand1 = Hand::new_with_joker("JKKK2 123", true);
-        let hand2 = Hand::new_with_joker("QQQQ2 123", true);
+        let hand1 = Hand::new_with_joker("JKKK2 123", true).unwrap();
+        let hand2 = Hand::new_with_joker("QQQQ2 123", true).unwrap();
         assert!(hand1 < hand2);
 
-        let hand3 = Hand::new_with_joker("T55J5 123", true);
-        let hand4 = Hand::new_with_joker("QQQJA 123", true);
-        let hand5 = Hand::new_with_joker("KTJJT 123", true);
+        let hand3 = Hand::new_with_joker("T55J5 123", true).unwrap();
+        let hand4 = Hand::new_with_joker("QQQJA 123", true).unwrap();
+        let hand5 = Hand::new_with_joker("KTJJT 123", true).unwrap();
         assert_eq!(hand3.win, Win::FourOfAKind(Rank::Five));
         assert_eq!(hand4.win, Win::FourOfAKind(Rank::Queen));
         assert_eq!(hand5.win, Win::FourOfAKind(Rank::Ten));
@@ -297,14 +593,266 @@ mod tests {
         assert!(hand4 < hand5);
         assert!(hand3 < hand5);
 
-        let hand6 = Hand::new_with_joker("JJJJJ 123", true);
-        let hand7 = Hand::new_with_joker("QQQQ2 123", true);
+        let hand6 = Hand::new_with_joker("JJJJJ 123", true).unwrap();
+        let hand7 = Hand::new_with_joker("QQQQ2 123", true).unwrap();
         assert!(hand6 > hand7);
 
-        let hand8 = Hand::new_with_joker("AJJJJ 123", true);
+        let hand8 = Hand::new_with_joker("AJJJJ 123", true).unwrap();
         assert_eq!(hand8.win, Win::FiveOfAKind(Rank::Ace));
 
-        let hand9 = Hand::new_with_joker("JJ4QK 123", true);
+        let hand9 = Hand::new_with_joker("JJ4QK 123", true).unwrap();
         assert_eq!(hand9.win, Win::ThreeOfAKind(Rank::King));
     }
+
+    #[test]
+    fn test_unknown_rank() {
+        let input = "32T3K 765\nKX677 28\n";
+        let error = Day07.parse(input).unwrap_err();
+        assert_eq!(error.line, Some(2));
+        assert_eq!(error.column, Some(2));
+        assert_eq!(error.text, "X");
+
+        assert_eq!(parse_ranks("2J"), Ok(vec![Rank::Two, Rank::Jack]));
+        assert_eq!(parse_ranks("J1").unwrap_err().message, "Unknown rank: 1");
+    }
+
+    fn poker(input: &str, rules: &Rules) -> Hand {
+        Hand::parse(&Parser::new(input), input, rules).unwrap()
+    }
+
+    #[test]
+    fn test_poker_wins() {
+        let rules = Rules::poker();
+        let royal = poker("ASKSQSJSTS 1", &rules);
+        assert_eq!(royal.win, Win::StraightFlush(Rank::Ace));
+        let wheel = poker("AH2D3C4S5H 1", &rules);
+        assert_eq!(wheel.win, Win::Straight(Rank::Five));
+        let flush = poker("2H4H6H8HTH 1", &rules);
+        assert_eq!(flush.win, Win::Flush(Rank::Ten));
+        let full_house = poker("KHKDKS2C2D 1", &rules);
+        assert_eq!(full_house.win, Win::FullHouse(Rank::King, Rank::Two));
+        let broadway = poker("AHKDQCJSTH 1", &rules);
+        assert_eq!(broadway.win, Win::Straight(Rank::Ace));
+
+        assert!(wheel < broadway);
+        assert!(broadway < flush);
+        assert!(flush < full_house);
+        assert!(full_house < royal);
+
+        // Camel Cards has no straights or flushes
+        assert_eq!(
+            royal.with_rules(&Rules::camel_cards()).win,
+            Win::HighCard(Rank::Ace)
+        );
+    }
+
+    #[test]
+    fn test_poker_kickers() {
+        let rules = Rules::poker();
+        let pair1 = poker("AHAD9C5S3H 1", &rules);
+        let pair2 = poker("ASAC9D5H2C 1", &rules);
+        assert!(pair1 > pair2);
+
+        let two_pair1 = poker("KHKD5C5S2H 1", &rules);
+        let two_pair2 = poker("3C5D5HKSKC 1", &rules);
+        assert!(two_pair1 < two_pair2);
+
+        // The pair outranks the higher first card
+        let pair3 = poker("AH3C3D5S7H 1", &rules);
+        let pair4 = poker("2C4H4S6D8C 1", &rules);
+        assert!(pair3 < pair4);
+        assert!(pair4.with_rules(&Rules::camel_cards()) < pair3.with_rules(&Rules::camel_cards()));
+    }
+
+    #[test]
+    fn test_poker_wildcards() {
+        let rules = Rules {
+            wild: vec![Rank::Two],
+            ..Rules::poker()
+        };
+        let hand = poker("2H5S6S7S8S 1", &rules);
+        assert_eq!(hand.win, Win::StraightFlush(Rank::Nine));
+        let hand = poker("2H2D9CKSKD 1", &rules);
+        assert_eq!(hand.win, Win::FourOfAKind(Rank::King));
+        let hand = poker("2H2D5C9SKD 1", &rules);
+        assert_eq!(hand.win, Win::ThreeOfAKind(Rank::King));
+        let hand = poker("2H2D2C2S2D 1", &rules);
+        assert_eq!(hand.win, Win::FiveOfAKind(Rank::Joker));
+    }
+
+    #[test]
+    fn test_poker_wildcard_kickers() {
+        let rules = Rules {
+            wild: vec![Rank::Two],
+            ..Rules::poker()
+        };
+        // The same wins tie, and then the wildcard counts as the lowest card
+        let pairs = [
+            ("KHKD2C4S3D 1", "KHKDKC4S3D 1"),
+            ("9H8D2C6S5H 1", "9H8D7C6S5H 1"),
+            ("AH2D5C5S9H 1", "AH5D5C5S9H 1"),
+            ("2H2D7C7S7H 1", "7D7H7C7S7H 1"),
+        ];
+        for (wild, natural) in pairs {
+            let (wild, natural) = (poker(wild, &rules), poker(natural, &rules));
+            assert_eq!(wild.win, natural.win);
+            assert_eq!(wild.strength, natural.strength, "{:?}", wild);
+            assert!(wild < natural, "{:?}", wild);
+        }
+
+        let straight = poker("9H8D2C6S5H 1", &rules);
+        assert_eq!(straight.strength.tie_break, [Rank::Nine]);
+        let three = poker("KHKD2C4S3D 1", &rules);
+        assert_eq!(
+            three.strength.tie_break,
+            [Rank::King, Rank::Four, Rank::Three]
+        );
+    }
+
+    #[test]
+    fn test_poker_wildcard_flushes() {
+        let rules = Rules {
+            wild: vec![Rank::Jack],
+            ..Rules::poker()
+        };
+        // The joker stands in for the ace of hearts
+        let joker = poker("JHQH9H5H3H 1", &rules);
+        assert_eq!(joker.win, Win::Flush(Rank::Ace));
+        let king = poker("KSQS9S5S3S 1", &rules);
+        assert_eq!(king.win, Win::Flush(Rank::King));
+        assert!(joker > king);
+
+        // With the ace held, the joker is the king
+        let ace = poker("ACJCTC7C4C 1", &rules);
+        let natural = poker("ADKDTD7D3D 1", &rules);
+        assert_eq!(ace.win, Win::Flush(Rank::Ace));
+        assert!(ace > natural);
+        let two_jokers = poker("JCJD9C5C3C 1", &rules);
+        assert_eq!(two_jokers.win, Win::Flush(Rank::Ace));
+        assert_eq!(
+            two_jokers.strength.tie_break,
+            [Rank::Ace, Rank::King, Rank::Nine, Rank::Five, Rank::Three]
+        );
+    }
+
+    #[test]
+    fn test_unknown_suit() {
+        let input = "AXKSQSJSTS 1";
+        let error = Hand::parse(&Parser::new(input), input, &Rules::poker()).unwrap_err();
+        assert_eq!(
+            (error.column, error.message.as_str()),
+            (Some(2), "Unknown suit")
+        );
+
+        let input = "ASKSQSJST 1";
+        let error = Hand::parse(&Parser::new(input), input, &Rules::poker()).unwrap_err();
+        assert_eq!(
+            (error.column, error.message.as_str()),
+            (Some(9), "Expected a suit")
+        );
+    }
+
+    // Every one of the 13^5 Camel Cards hands, with bids telling them apart
+    // from hands with the same cards.
+    fn every_hand(rules: &Rules) -> Vec<Hand> {
+        (0..13u32.pow(5))
+            .map(|index| {
+                let mut n = index as usize;
+                let cards = (0..5)
+                    .map(|_| {
+                        let rank = Rank::ALL[n % 13];
+                        n /= 13;
+                        Card { rank, suit: None }.played(rules)
+                    })
+                    .collect();
+                Hand::new_played(cards, rules, index % 7)
+            })
+            .collect()
+    }
+
+    // The category of the ranks counted, trying every rank already in the
+    // hand, or an ace, for each joker
+    fn category_brute_force(counts: &mut [u8; 15], jokers: u8) -> Category {
+        if jokers > 0 {
+            let mut candidates: Vec<_> = Rank::ALL
+                .iter()
+                .filter(|&&r| counts[r as usize] > 0)
+                .collect();
+            candidates.push(&Rank::Ace);
+            return candidates
+                .into_iter()
+                .map(|&rank| {
+                    counts[rank as usize] += 1;
+                    let category = category_brute_force(counts, jokers - 1);
+                    counts[rank as usize] -= 1;
+                    category
+                })
+                .max()
+                .unwrap();
+        }
+
+        let mut sizes: Vec<_> = counts.iter().copied().filter(|&n| n > 0).collect();
+        sizes.sort_by(|a, b| b.cmp(a));
+        match sizes[..] {
+            [5] => Category::FiveOfAKind,
+            [4, 1] => Category::FourOfAKind,
+            [3, 2] => Category::FullHouse,
+            [3, 1, 1] => Category::ThreeOfAKind,
+            [2, 2, 1] => Category::TwoPair,
+            [2, 1, 1, 1] => Category::OnePair,
+            _ => Category::HighCard,
+        }
+    }
+
+    fn hash(hand: &Hand) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hand.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn check_every_hand(rules: &Rules) {
+        let mut hands = every_hand(rules);
+
+        for hand in &hands {
+            let mut counts = [0; 15];
+            for card in &hand.cards {
+                counts[card.rank as usize] += 1;
+            }
+            let jokers = std::mem::take(&mut counts[Rank::Joker as usize]);
+            let category = category_brute_force(&mut counts, jokers);
+            assert_eq!(hand.strength.category, category, "{:?}", hand);
+            assert_eq!(hand.win.category(), hand.strength.category);
+        }
+
+        // Rebuilding a hand gives one equal to it, with the same hash
+        for hand in hands.iter().step_by(101) {
+            let copy = Hand::new_played(hand.cards.clone(), rules, hand.bid);
+            assert_eq!(hand, &copy);
+            assert_eq!(hand.cmp(&copy), Ordering::Equal);
+            assert_eq!(hash(hand), hash(&copy));
+
+            let other = Hand::new_played(hand.cards.clone(), rules, hand.bid + 1);
+            assert_ne!(hand, &other);
+            assert_eq!(hand.cmp(&other), Ordering::Less);
+        }
+
+        // Sorted hands are strictly increasing, and differ from every neighbour
+        hands.sort();
+        for pair in hands.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            assert_eq!(a.cmp(b), Ordering::Less, "{:?} {:?}", a, b);
+            assert_eq!(b.cmp(a), Ordering::Greater, "{:?} {:?}", a, b);
+            assert!(a != b && a.strength <= b.strength);
+        }
+    }
+
+    #[test]
+    fn test_every_hand() {
+        check_every_hand(&Rules::camel_cards());
+    }
+
+    #[test]
+    fn test_every_hand_with_jokers() {
+        check_every_hand(&Rules::camel_cards_with_jokers());
+    }
 }
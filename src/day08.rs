@@ -1,75 +1,671 @@
+use crate::error::{Error, Result};
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
+use regex::Regex;
 use std::collections::HashMap;
+use std::fmt;
 
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/08.txt").unwrap();
-    let (instructions, net_str) = input.trim().split_once("\n\n").unwrap();
+/// The characters instructions are written with. The instruction at index `i`
+/// follows the `i`th child listed for a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: Vec<char>,
+}
 
-    let mut network: HashMap<&str, (&str, &str)> = HashMap::new();
-    for line in net_str.lines() {
-        let (node, pairstr) = line.split_once(" = (").unwrap();
-        network.insert(node, pairstr[..pairstr.len() - 1].split_once(", ").unwrap());
-    }
-
-    let mut steps = 0;
-    let mut next = "AAA";
-    for instruction in instructions.chars().cycle() {
-        let pair = network[next];
-        match instruction {
-            'L' => next = pair.0,
-            'R' => next = pair.1,
-            _ => panic!("Unknown instruction"),
+impl Alphabet {
+    pub fn new(symbols: &str) -> Self {
+        Alphabet {
+            symbols: symbols.chars().collect(),
         }
-        steps += 1;
-        if next == "ZZZ" {
-            break;
+    }
+
+    /// Left and right, the alphabet of the puzzle.
+    pub fn left_right() -> Self {
+        Self::new("LR")
+    }
+
+    /// The number of children every node has.
+    pub fn arity(&self) -> usize {
+        self.symbols.len()
+    }
+
+    fn index(&self, symbol: char) -> Option<usize> {
+        self.symbols.iter().position(|&s| s == symbol)
+    }
+}
+
+/// Picks out nodes by name.
+#[derive(Clone, Debug)]
+pub enum Matcher {
+    Exact(String),
+    Suffix(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let re = Regex::new(pattern)
+            .map_err(|_| Error::new(format!("Invalid node pattern: {}", pattern)))?;
+        Ok(Matcher::Regex(re))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Matcher::Exact(exact) => name == exact,
+            Matcher::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            Matcher::Regex(re) => re.is_match(name),
         }
     }
+}
 
-    println!("08 - Part One: {}", steps);
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Matcher::Exact(exact) => write!(f, "{}", exact),
+            Matcher::Suffix(suffix) => write!(f, "ending in {}", suffix),
+            Matcher::Regex(re) => write!(f, "matching /{}/", re),
+        }
+    }
 }
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/08.txt").unwrap();
-    let (instructions, net_str) = input.trim().split_once("\n\n").unwrap();
+pub type NodeId = u32;
+
+/// The nodes of the network numbered in the order they are defined, with the
+/// children of each.
+pub struct Network<'a> {
+    names: Vec<&'a str>,
+    arity: usize,
+    // The children of node `n` are at `n * arity..(n + 1) * arity`
+    children: Vec<NodeId>,
+}
+
+impl<'a> Network<'a> {
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn name(&self, node: NodeId) -> &'a str {
+        self.names[node as usize]
+    }
+
+    #[cfg(test)]
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.names
+            .iter()
+            .position(|&n| n == name)
+            .map(|id| id as NodeId)
+    }
+
+    /// The nodes whose names match `matcher`, in order.
+    pub fn nodes(&self, matcher: &Matcher) -> Vec<NodeId> {
+        (0..self.len() as NodeId)
+            .filter(|&node| matcher.matches(self.name(node)))
+            .collect()
+    }
+
+    /// Whether each node's name matches `matcher`, indexed by node.
+    pub fn mask(&self, matcher: &Matcher) -> Vec<bool> {
+        self.names
+            .iter()
+            .map(|name| matcher.matches(name))
+            .collect()
+    }
+
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        let start = node as usize * self.arity;
+        &self.children[start..start + self.arity]
+    }
+
+    fn next(&self, node: NodeId, instruction: usize) -> NodeId {
+        self.children[node as usize * self.arity + instruction]
+    }
+}
 
-    let mut network: HashMap<&str, (&str, &str)> = HashMap::new();
+pub struct Maps<'a> {
+    alphabet: Alphabet,
+    // Indices into the alphabet
+    instructions: Vec<usize>,
+    network: Network<'a>,
+}
+
+impl Maps<'_> {
+    /// Walk from `start`, calling `visit` with every step and the node it is
+    /// on, until back on a node at the same point in the instructions.
+    /// Returns the step that state was first reached at and the step it was
+    /// reached again.
+    fn walk(&self, start: NodeId, mut visit: impl FnMut(usize, NodeId)) -> (usize, usize) {
+        let period = self.instructions.len();
+        // The step each (node, instruction index) state was first reached at
+        let mut seen = vec![usize::MAX; self.network.len() * period];
+        let mut node = start;
+
+        for step in 0.. {
+            let index = step % period;
+            let state = node as usize * period + index;
+            if seen[state] != usize::MAX {
+                return (seen[state], step);
+            }
+            seen[state] = step;
+            visit(step, node);
+
+            node = self.network.next(node, self.instructions[index]);
+        }
+        unreachable!()
+    }
+
+    /// The first step at which a ghost starting on every node matched by
+    /// `start` is on a node matched by `end`, or `None` if that never happens.
+    pub fn steps(&self, start: &Matcher, end: &Matcher) -> Result<Option<u128>> {
+        let starts = self.network.nodes(start);
+        if starts.is_empty() {
+            return Err(Error::new(format!("No node {} in the network", start)));
+        }
+
+        let is_end = self.network.mask(end);
+        let cycles: Vec<_> = starts
+            .into_iter()
+            .map(|start| Cycle::find(self, start, &is_end))
+            .collect();
+        synchronise(&cycles)
+    }
+}
+
+pub fn parse_maps<'a>(input: &'a str, alphabet: &Alphabet) -> Result<Maps<'a>> {
+    let parser = Parser::new(input);
+    let (instructions_str, net_str) = parser.split_once(input.trim(), "\n\n")?;
+
+    let mut instructions = vec![];
+    for (index, c) in instructions_str.char_indices() {
+        let Some(instruction) = alphabet.index(c) else {
+            let text = &instructions_str[index..index + c.len_utf8()];
+            return Err(parser.error(text, "Unknown instruction"));
+        };
+        instructions.push(instruction);
+    }
+    if instructions.is_empty() {
+        return Err(parser.error(instructions_str, "Expected instructions"));
+    }
+
+    let mut entries = vec![];
+    let mut ids: HashMap<&str, NodeId> = HashMap::new();
     for line in net_str.lines() {
-        let (node, pairstr) = line.split_once(" = (").unwrap();
-        network.insert(node, pairstr[..pairstr.len() - 1].split_once(", ").unwrap());
+        let (node, children_str) = parser.split_once(line, " = (")?;
+        let children_str = parser.strip_suffix(children_str, ")")?;
+        if ids.insert(node, entries.len() as NodeId).is_some() {
+            return Err(parser.error(node, "Duplicate node"));
+        }
+        let children: Vec<_> = children_str.split(", ").collect();
+        if children.len() != alphabet.arity() {
+            let message = format!("Expected {} children", alphabet.arity());
+            return Err(parser.error(children_str, message));
+        }
+        entries.push((node, children));
+    }
+    if entries.len() > NodeId::MAX as usize {
+        return Err(Error::new("Too many nodes in the network"));
     }
 
-    let mut cursors: Vec<Vec<&str>> = vec![];
-    for node in network.keys() {
-        if node.ends_with('A') {
-            cursors.push(vec![node]);
+    let mut network = Network {
+        names: vec![],
+        arity: alphabet.arity(),
+        children: vec![],
+    };
+    for (node, children) in entries {
+        network.names.push(node);
+        for child in children {
+            let id = ids
+                .get(child)
+                .ok_or_else(|| parser.error(child, "Unknown node"))?;
+            network.children.push(*id);
         }
     }
 
-    let mut cycle_lengths = vec![];
-    for (step, instruction) in instructions.chars().cycle().enumerate() {
-        for cursor in &mut cursors {
-            let mut next = cursor.last().unwrap();
-            if next.ends_with('Z') {
-                continue;
+    Ok(Maps {
+        alphabet: alphabet.clone(),
+        instructions,
+        network,
+    })
+}
+
+/// The path of a ghost, which repeats once it is back on a node at the same
+/// point in the instructions.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cycle {
+    /// Steps taken before the path starts repeating
+    pub tail: usize,
+    /// Steps taken by each repetition
+    pub length: usize,
+    /// Steps before `tail + length` at which the ghost is on an end node.
+    /// Those from `tail` onwards recur every `length` steps.
+    pub hits: Vec<usize>,
+}
+
+impl Cycle {
+    /// Walk from `start` until the path repeats, where `is_end` tells which
+    /// nodes are end nodes.
+    fn find(maps: &Maps, start: NodeId, is_end: &[bool]) -> Self {
+        let mut hits = vec![];
+        let (tail, end) = maps.walk(start, |step, node| {
+            if is_end[node as usize] {
+                hits.push(step);
             }
-            let pair = network[next];
-            match instruction {
-                'L' => next = &pair.0,
-                'R' => next = &pair.1,
-                _ => panic!("Unknown instruction"),
+        });
+        Cycle {
+            tail,
+            length: end - tail,
+            hits,
+        }
+    }
+
+    /// Whether the ghost is on an end node after `step` steps.
+    fn hits(&self, step: u128) -> bool {
+        let tail = self.tail as u128;
+        let step = if step < tail {
+            step
+        } else {
+            tail + (step - tail) % self.length as u128
+        };
+        self.hits.contains(&(step as usize))
+    }
+}
+
+// Solve x = a (mod m) and x = b (mod n), giving x modulo lcm(m, n), or
+// `None` if there is no solution.
+fn crt((a, m): (i128, i128), (b, n): (i128, i128)) -> Result<Option<(i128, i128)>> {
+    let too_many = || Error::new("Ghosts take too many steps to synchronise");
+    let (g, p, _) = extended_gcd(m, n);
+    if (b - a) % g != 0 {
+        return Ok(None);
+    }
+    let lcm = (m / g).checked_mul(n).ok_or_else(too_many)?;
+    let k = ((b - a) / g % (n / g))
+        .checked_mul(p)
+        .ok_or_else(too_many)?
+        % (n / g);
+    let x = m
+        .checked_mul(k)
+        .and_then(|mk| a.checked_add(mk))
+        .ok_or_else(too_many)?
+        .rem_euclid(lcm);
+    Ok(Some((x, lcm)))
+}
+
+// Returns (g, x, y) with a * x + b * y = g = gcd(a, b).
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - a / b * y)
+    }
+}
+
+// The most combinations of recurring hits kept while synchronising ghosts
+const MAX_SOLUTIONS: usize = 1 << 20;
+
+/// The first step at which every ghost is on an end node at once, or `None`
+/// if that never happens.
+pub fn synchronise(cycles: &[Cycle]) -> Result<Option<u128>> {
+    let Some(latest) = cycles.iter().max_by_key(|c| c.tail) else {
+        return Ok(None);
+    };
+
+    // Before the last tail ends, the steps to check are the hits in it
+    let mut early = latest.hits.iter().take_while(|&&step| step < latest.tail);
+    if let Some(&step) = early.find(|&&step| cycles.iter().all(|c| c.hits(step as u128))) {
+        return Ok(Some(step as u128));
+    }
+
+    // Afterwards every ghost repeats, so combine their recurring hits
+    let mut solutions = vec![(0, 1)];
+    for cycle in cycles {
+        let residues = cycle.hits.iter().filter(|&&step| step >= cycle.tail);
+        let mut combined = vec![];
+        for &step in residues {
+            let residue = (step as i128, cycle.length as i128);
+            for &solution in &solutions {
+                if let Some(solution) = crt(solution, residue)? {
+                    combined.push(solution);
+                }
+            }
+            if combined.len() > MAX_SOLUTIONS {
+                return Err(Error::new("Ghosts have too many ways to synchronise"));
             }
-            cursor.push(next);
-            if next.ends_with('Z') {
-                cycle_lengths.push(step + 1);
+        }
+        // Reject moduli too large to work with rather than overflow
+        if combined.iter().any(|&(_, m)| m > u64::MAX as i128) {
+            return Err(Error::new("Ghosts take too many steps to synchronise"));
+        }
+        combined.sort();
+        combined.dedup();
+        solutions = combined;
+    }
+
+    let start = latest.tail as i128;
+    let first = solutions
+        .into_iter()
+        .map(|(x, m)| {
+            // The first step from `start` on with the right remainder
+            start + (x - start).rem_euclid(m)
+        })
+        .min();
+    Ok(first.map(|step| step as u128))
+}
+
+const PATH_COLOURS: [&str; 6] = ["blue", "orange", "purple", "brown", "deeppink", "teal"];
+
+/// Render the network as a Graphviz graph, with the nodes matched by `start`
+/// and `end` filled in. With `paths`, the edges taken by the ghost from each
+/// start node are drawn in its own colour, up to where its path repeats.
+pub fn to_dot(maps: &Maps, start: &Matcher, end: &Matcher, paths: bool) -> String {
+    let network = &maps.network;
+    let mut lines = vec![String::from("digraph network {")];
+
+    for node in 0..network.len() as NodeId {
+        let name = network.name(node);
+        if start.matches(name) {
+            lines.push(format!("  {:?} [style=filled, fillcolor=palegreen];", name));
+        } else if end.matches(name) {
+            lines.push(format!("  {:?} [style=filled, fillcolor=salmon];", name));
+        }
+    }
+
+    // One edge to each child, labelled with every instruction leading to it
+    for node in 0..network.len() as NodeId {
+        let mut edges: Vec<(NodeId, Vec<String>)> = vec![];
+        for (&child, symbol) in network.children(node).iter().zip(&maps.alphabet.symbols) {
+            match edges.iter_mut().find(|(c, _)| *c == child) {
+                Some((_, symbols)) => symbols.push(symbol.to_string()),
+                None => edges.push((child, vec![symbol.to_string()])),
             }
         }
-        if cycle_lengths.len() == cursors.len() {
-            break;
+        for (child, symbols) in edges {
+            lines.push(format!(
+                "  {:?} -> {:?} [label={:?}];",
+                network.name(node),
+                network.name(child),
+                symbols.join(",")
+            ));
         }
     }
 
-    let steps = cycle_lengths
-        .iter()
-        .fold(1, |x, y| num::integer::lcm(x, *y));
-    println!("08 - Part One: {}", steps);
+    if paths {
+        let starts = network.nodes(start);
+        for (start, colour) in starts.into_iter().zip(PATH_COLOURS.iter().cycle()) {
+            let mut path = vec![];
+            let (_, end) = maps.walk(start, |_, node| path.push(node));
+            // Close the loop with the step back to the repeated state
+            let instruction = maps.instructions[(end - 1) % maps.instructions.len()];
+            path.push(network.next(path[end - 1], instruction));
+
+            let mut edges: Vec<_> = path.windows(2).map(|pair| (pair[0], pair[1])).collect();
+            edges.sort();
+            edges.dedup();
+            for (from, to) in edges {
+                lines.push(format!(
+                    "  {:?} -> {:?} [color={}, penwidth=2];",
+                    network.name(from),
+                    network.name(to),
+                    colour
+                ));
+            }
+        }
+    }
+
+    lines.push(String::from("}"));
+    lines.join("\n") + "\n"
+}
+
+/// The start and end nodes of the ghosts in part two.
+pub fn ghosts() -> (Matcher, Matcher) {
+    (
+        Matcher::Suffix(String::from("A")),
+        Matcher::Suffix(String::from("Z")),
+    )
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input<'a> = Maps<'a>;
+    type View<'a> = Maps<'a>;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        parse_maps(input, &Alphabet::left_right())
+    }
+
+    fn part_one(&self, maps: &Maps) -> Result<Answer> {
+        let start = Matcher::Exact(String::from("AAA"));
+        let end = Matcher::Exact(String::from("ZZZ"));
+        let steps = maps
+            .steps(&start, &end)?
+            .ok_or_else(|| Error::new("Node ZZZ is never reached from AAA"))?;
+
+        Ok(steps.into())
+    }
+
+    fn part_two(&self, maps: &Maps) -> Result<Answer> {
+        let (start, end) = ghosts();
+        let steps = maps
+            .steps(&start, &end)?
+            .ok_or_else(|| Error::new("The ghosts are never all on end nodes at once"))?;
+        Ok(steps.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Ok(Some((8, 15))));
+        assert_eq!(crt((0, 2), (1, 3)), Ok(Some((4, 6))));
+        assert_eq!(crt((1, 4), (3, 6)), Ok(Some((9, 12))));
+        assert_eq!(crt((1, 4), (2, 6)), Ok(None));
+
+        // Overflow is an error rather than a pair that never coincides
+        let large = 1 << 100;
+        let error = crt((0, large), (1, large + 1)).unwrap_err();
+        assert_eq!(error.message, "Ghosts take too many steps to synchronise");
+    }
+
+    #[test]
+    fn test_cycles() {
+        // 11A enters a loop of two after a step, 22A a loop of three
+        let input = "\
+L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+33A = (33Z, 33Z)
+33Z = (33B, 33B)
+33B = (33Z, 33Z)
+";
+        let maps = parse_maps(input, &Alphabet::left_right()).unwrap();
+        let network = &maps.network;
+        let is_end = network.mask(&ghosts().1);
+        let start = |name| network.find(name).unwrap();
+        let first = Cycle::find(&maps, start("11A"), &is_end);
+        assert_eq!(
+            first,
+            Cycle {
+                tail: 1,
+                length: 2,
+                hits: vec![2]
+            }
+        );
+        let second = Cycle::find(&maps, start("22A"), &is_end);
+        assert_eq!(
+            second,
+            Cycle {
+                tail: 1,
+                length: 3,
+                hits: vec![1]
+            }
+        );
+        let third = Cycle::find(&maps, start("33A"), &is_end);
+
+        // Taking the LCM of the first hits would give 2
+        assert_eq!(synchronise(&[first, second]).unwrap(), Some(4));
+
+        // The first ghost is on an end node on even steps, the third on odd ones
+        let first = Cycle::find(&maps, start("11A"), &is_end);
+        assert_eq!(synchronise(&[first, third]).unwrap(), None);
+        let error = Day08.part_two(&maps).unwrap_err();
+        assert_eq!(
+            error.message,
+            "The ghosts are never all on end nodes at once"
+        );
+    }
+
+    #[test]
+    fn test_synchronise_in_tail() {
+        let tail = |hits: Vec<usize>| Cycle {
+            tail: 10,
+            length: 4,
+            hits,
+        };
+        let cycles = [tail(vec![3, 7, 11]), tail(vec![5, 7])];
+        assert_eq!(synchronise(&cycles).unwrap(), Some(7));
+        let cycles = [tail(vec![3, 11]), tail(vec![5, 11, 12])];
+        assert_eq!(synchronise(&cycles).unwrap(), Some(11));
+    }
+
+    #[test]
+    fn test_synchronise_too_many_hits() {
+        let cycle = |length: usize, hits: usize| Cycle {
+            tail: 0,
+            length,
+            hits: (0..hits).collect(),
+        };
+        let cycles = [cycle(1103, 1100), cycle(1009, 1000)];
+        let error = synchronise(&cycles).unwrap_err();
+        assert_eq!(error.message, "Ghosts have too many ways to synchronise");
+    }
+
+    #[test]
+    fn test_unreachable_end() {
+        let input = "LR\n\nAAA = (AAA, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n";
+        let maps = parse_maps(input, &Alphabet::left_right()).unwrap();
+        let error = Day08.part_one(&maps).unwrap_err();
+        assert_eq!(error.message, "Node ZZZ is never reached from AAA");
+    }
+
+    #[test]
+    fn test_network() {
+        let input =
+            "LR\n\nAAA = (BBB, CCC)\nBBB = (AAA, ZZZ)\nCCC = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\n";
+        let network = parse_maps(input, &Alphabet::left_right()).unwrap().network;
+        assert_eq!(network.len(), 4);
+        assert_eq!(network.find("CCC"), Some(2));
+        assert_eq!(network.name(3), "ZZZ");
+        assert_eq!(network.children, [1, 2, 0, 3, 3, 3, 3, 3]);
+        assert_eq!(network.children(1), [0, 3]);
+        let matcher = Matcher::regex("^[AB]").unwrap();
+        assert_eq!(network.nodes(&matcher), [0, 1]);
+        let error = Matcher::regex("[AB").unwrap_err();
+        assert_eq!(error.message, "Invalid node pattern: [AB");
+        assert_eq!(network.mask(&ghosts().1), [false, false, false, true]);
+
+        let input = "L\n\nAAA = (AAA, BBB)\nAAA = (AAA, AAA)\n";
+        let error = parse_maps(input, &Alphabet::left_right()).err().unwrap();
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (Some(4), "Duplicate node")
+        );
+
+        let input = "L\n\nAAA = (AAA, BBB)\n";
+        let error = parse_maps(input, &Alphabet::left_right()).err().unwrap();
+        assert_eq!(
+            (error.text.as_str(), error.message.as_str()),
+            ("BBB", "Unknown node")
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let input =
+            "LR\n\n11A = (11B, XXX)\n11B = (XXX, 11Z)\n11Z = (11B, XXX)\nXXX = (XXX, XXX)\n";
+        let maps = parse_maps(input, &Alphabet::left_right()).unwrap();
+        let (start, end) = ghosts();
+        assert_eq!(
+            to_dot(&maps, &start, &end, false),
+            "\
+digraph network {
+  \"11A\" [style=filled, fillcolor=palegreen];
+  \"11Z\" [style=filled, fillcolor=salmon];
+  \"11A\" -> \"11B\" [label=\"L\"];
+  \"11A\" -> \"XXX\" [label=\"R\"];
+  \"11B\" -> \"XXX\" [label=\"L\"];
+  \"11B\" -> \"11Z\" [label=\"R\"];
+  \"11Z\" -> \"11B\" [label=\"L\"];
+  \"11Z\" -> \"XXX\" [label=\"R\"];
+  \"XXX\" -> \"XXX\" [label=\"L,R\"];
+}
+"
+        );
+
+        // The ghost goes 11A, 11B, 11Z and back to 11B at the same instruction
+        let dot = to_dot(&maps, &start, &end, true);
+        let paths: Vec<_> = dot.lines().filter(|l| l.contains("penwidth")).collect();
+        assert_eq!(
+            paths,
+            [
+                "  \"11A\" -> \"11B\" [color=blue, penwidth=2];",
+                "  \"11B\" -> \"11Z\" [color=blue, penwidth=2];",
+                "  \"11Z\" -> \"11B\" [color=blue, penwidth=2];",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alphabets_and_matchers() {
+        // Three children per node, picked by a, b and c
+        let input = "\
+abcc
+
+start = (x1, x2, end1)
+x1 = (x1, start, x2)
+x2 = (end1, end2, x1)
+end1 = (x2, x2, x2)
+end2 = (end2, end2, end2)
+";
+        let alphabet = Alphabet::new("abc");
+        let maps = parse_maps(input, &alphabet).unwrap();
+        assert_eq!(maps.instructions, [0, 1, 2, 2]);
+
+        // start, x1, start, end1, x2, end1, ...
+        let exact = |name: &str| Matcher::Exact(name.to_string());
+        assert_eq!(
+            maps.steps(&exact("start"), &exact("end1")).unwrap(),
+            Some(3)
+        );
+        let ends = Matcher::regex("^end[0-9]$").unwrap();
+        assert_eq!(maps.steps(&exact("x2"), &ends).unwrap(), Some(1));
+        assert_eq!(maps.steps(&exact("end2"), &ends).unwrap(), Some(0));
+        assert_eq!(maps.steps(&exact("end2"), &exact("start")).unwrap(), None);
+
+        let error = maps.steps(&Matcher::Suffix(String::from("A")), &ends);
+        assert_eq!(
+            error.unwrap_err().message,
+            "No node ending in A in the network"
+        );
+
+        let dot = to_dot(&maps, &exact("start"), &ends, false);
+        assert!(dot.contains("  \"x2\" -> \"x1\" [label=\"c\"];"));
+        assert!(dot.contains("  \"end1\" -> \"x2\" [label=\"a,b,c\"];"));
+
+        let error = parse_maps(input, &Alphabet::left_right()).err().unwrap();
+        assert_eq!((error.line, error.column), (Some(1), Some(1)));
+        let error = parse_maps("ab\n\nx = (x, x, x)\n", &Alphabet::new("ab"))
+            .err()
+            .unwrap();
+        assert_eq!(error.message, "Expected 2 children");
+    }
 }
@@ -1,66 +1,181 @@
-fn subseq(seq: &[i32]) -> Vec<i32> {
+use crate::error::Result;
+use crate::parser::Parser;
+use crate::solution::{Answer, Solution};
+use num::{BigInt, Zero};
+
+fn subseq(seq: &[BigInt]) -> Vec<BigInt> {
     seq.windows(2)
-        .map(|window| window[1] - window[0])
-        .collect::<Vec<i32>>()
+        .map(|window| &window[1] - &window[0])
+        .collect::<Vec<BigInt>>()
 }
 
-pub fn solve_part_one() {
-    let input = std::fs::read_to_string("input/09.txt").unwrap();
-    let seqs = input
-        .lines()
-        .map(|line| {
-            line.split(' ')
-                .map(|token| token.parse::<i32>().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-
-    let mut sum = 0;
-    for seq in seqs {
-        let mut subseqs: Vec<Vec<i32>> = vec![seq];
-        loop {
-            let subseq = subseq(subseqs.last().unwrap());
-            if subseq.iter().all(|n| *n == 0) {
-                subseqs.push(subseq);
-                break;
-            }
-            subseqs.push(subseq);
+/// The polynomial of lowest degree through a sequence, sampled at the indices
+/// `0, 1, 2, ...`, in Newton's forward difference form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polynomial {
+    // The first value of every row of the difference table, without the
+    // trailing zeros
+    differences: Vec<BigInt>,
+    len: usize,
+}
+
+impl Polynomial {
+    pub fn fit(seq: &[BigInt]) -> Self {
+        let mut differences = vec![];
+        let mut row = seq.to_vec();
+        while row.iter().any(|n| !n.is_zero()) {
+            differences.push(row[0].clone());
+            row = subseq(&row);
+        }
+        Polynomial {
+            differences,
+            len: seq.len(),
         }
+    }
+
+    /// The number of values the polynomial was fitted to.
+    pub fn len(&self) -> usize {
+        self.len
+    }
 
-        let next = subseqs.iter().rfold(0, |sum, ss| sum + ss.last().unwrap());
-        sum += next;
+    /// The degree of the polynomial, or `None` for a sequence of zeros.
+    pub fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
     }
 
-    println!("09 - Part One: {}", sum);
+    /// The value at index `k`, which may be negative or past the end of the
+    /// sequence.
+    pub fn at(&self, k: &BigInt) -> BigInt {
+        // Sum the differences times the binomial coefficients (k choose j),
+        // each exactly divisible as it is built up
+        let mut value = BigInt::zero();
+        let mut binomial = BigInt::from(1);
+        for (j, difference) in self.differences.iter().enumerate() {
+            if j > 0 {
+                binomial = binomial * (k - (j - 1)) / j;
+            }
+            value += difference * &binomial;
+        }
+        value
+    }
 }
 
-pub fn solve_part_two() {
-    let input = std::fs::read_to_string("input/09.txt").unwrap();
-    let seqs = input
+fn parse_polynomials(input: &str) -> Result<Vec<Polynomial>> {
+    let parser = Parser::new(input);
+    input
         .lines()
         .map(|line| {
-            line.split(' ')
-                .map(|token| token.parse::<i32>().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-
-    let mut sum = 0;
-    for mut seq in seqs {
-        seq.reverse();
-        let mut subseqs: Vec<Vec<i32>> = vec![seq];
-        loop {
-            let subseq = subseq(subseqs.last().unwrap());
-            if subseq.iter().all(|n| *n == 0) {
-                subseqs.push(subseq);
-                break;
+            let seq: Vec<BigInt> = parser.numbers(line)?;
+            if seq.is_empty() {
+                return Err(parser.error(line, "Expected a sequence of numbers"));
             }
-            subseqs.push(subseq);
-        }
 
-        let next = subseqs.iter().rfold(0, |sum, ss| sum + ss.last().unwrap());
-        sum += next;
+            // Unless the differences reach a row of zeros, any next value
+            // would fit the sequence just as well
+            let poly = Polynomial::fit(&seq);
+            if let Some(degree) = poly.degree().filter(|&degree| degree + 1 >= seq.len()) {
+                let message = format!(
+                    "Expected the differences to reach zero, but the sequence has degree {}",
+                    degree
+                );
+                return Err(parser.error(line, message));
+            }
+            Ok(poly)
+        })
+        .collect()
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input<'a> = Vec<Polynomial>;
+    type View<'a> = [Polynomial];
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        parse_polynomials(input)
     }
 
-    println!("09 - Part Two: {}", sum);
+    fn part_one(&self, polys: &[Polynomial]) -> Result<Answer> {
+        let sum: BigInt = polys
+            .iter()
+            .map(|poly| poly.at(&BigInt::from(poly.len())))
+            .sum();
+
+        Ok(sum.into())
+    }
+
+    fn part_two(&self, polys: &[Polynomial]) -> Result<Answer> {
+        let sum: BigInt = polys.iter().map(|poly| poly.at(&BigInt::from(-1))).sum();
+
+        Ok(sum.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fit(seq: &[i64]) -> Polynomial {
+        let seq: Vec<BigInt> = seq.iter().map(|&n| BigInt::from(n)).collect();
+        Polynomial::fit(&seq)
+    }
+
+    fn at(poly: &Polynomial, k: i64) -> BigInt {
+        poly.at(&BigInt::from(k))
+    }
+
+    #[test]
+    fn test_polynomial() {
+        let poly = fit(&[10, 13, 16, 21, 30, 45]);
+        assert_eq!(poly.degree(), Some(3));
+        assert_eq!(poly.len(), 6);
+        assert_eq!(at(&poly, 6), BigInt::from(68));
+        assert_eq!(at(&poly, -1), BigInt::from(5));
+
+        // n^2 + 1 far ahead and far behind
+        let poly = fit(&[1, 2, 5, 10, 17]);
+        assert_eq!(poly.degree(), Some(2));
+        assert_eq!(at(&poly, 1000), BigInt::from(1_000_001));
+        assert_eq!(at(&poly, -1000), BigInt::from(1_000_001));
+
+        assert_eq!(fit(&[7, 7, 7]).degree(), Some(0));
+        assert_eq!(fit(&[0, 0]).degree(), None);
+        assert_eq!(at(&fit(&[0, 0]), 5), BigInt::zero());
+        assert_eq!(at(&fit(&[4]), -3), BigInt::from(4));
+    }
+
+    #[test]
+    fn test_steep_sequences() {
+        // 10^n overflows i64 long before the extrapolated values
+        let seq: Vec<BigInt> = (0..21u32).map(|n| BigInt::from(10).pow(n)).collect();
+        let poly = Polynomial::fit(&seq);
+        assert_eq!(poly.degree(), Some(20));
+        // The fitted polynomial is sum_j 9^j C(n, j), which agrees with 10^n
+        // up to n = 20 and falls short by 9^21 at n = 21
+        let expected = BigInt::from(10).pow(21) - BigInt::from(9).pow(21);
+        assert_eq!(at(&poly, 21), expected);
+
+        let seq: Vec<BigInt> = (0..30i64).map(|n| BigInt::from(n).pow(12) * -3).collect();
+        let poly = Polynomial::fit(&seq);
+        assert_eq!(poly.degree(), Some(12));
+        assert_eq!(at(&poly, 1_000_000), BigInt::from(1_000_000).pow(12) * -3);
+        assert_eq!(at(&poly, -7), BigInt::from(7).pow(12) * -3);
+    }
+
+    #[test]
+    fn test_parse_polynomials() {
+        let polys = parse_polynomials("0 0\n1 2 3\n5 5\n").unwrap();
+        let degrees: Vec<_> = polys.iter().map(Polynomial::degree).collect();
+        assert_eq!(degrees, [None, Some(1), Some(0)]);
+
+        let error = parse_polynomials("1 2 3\n\n").unwrap_err();
+        assert_eq!(error.message, "Expected a sequence of numbers");
+        assert_eq!(error.line, Some(2));
+
+        let error = parse_polynomials("1 2 4\n").unwrap_err();
+        assert_eq!(
+            error.message,
+            "Expected the differences to reach zero, but the sequence has degree 2"
+        );
+    }
 }
@@ -0,0 +1,104 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error in a puzzle input, with the position of the offending text when
+/// it is known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub day: Option<u8>,
+    /// 1-based line number in the input
+    pub line: Option<usize>,
+    /// 1-based column in characters
+    pub column: Option<usize>,
+    pub text: String,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(message: impl Into<String>) -> Self {
+        Error {
+            day: None,
+            line: None,
+            column: None,
+            text: String::new(),
+            message: message.into(),
+        }
+    }
+
+    /// Create an error pointing at `text`, which must be a slice of `input`
+    /// for the position to be known.
+    pub fn at(input: &str, text: &str, message: impl Into<String>) -> Self {
+        let mut error = Self::new(message);
+        error.text = text.to_string();
+
+        let start = input.as_ptr() as usize;
+        let position = text.as_ptr() as usize;
+        if position >= start && position + text.len() <= start + input.len() {
+            let before = &input[..position - start];
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            error.line = Some(before.matches('\n').count() + 1);
+            error.column = Some(before[line_start..].chars().count() + 1);
+        }
+        error
+    }
+
+    pub fn in_day(mut self, day: u8) -> Self {
+        self.day = Some(day);
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut location = vec![];
+        if let Some(day) = self.day {
+            location.push(format!("day {:02}", day));
+        }
+        if let Some(line) = self.line {
+            location.push(format!("line {}", line));
+        }
+        if let Some(column) = self.column {
+            location.push(format!("column {}", column));
+        }
+        if !location.is_empty() {
+            write!(f, "{}: ", location.join(", "))?;
+        }
+
+        write!(f, "{}", self.message)?;
+        if !self.text.is_empty() {
+            write!(f, ": {:?}", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_position() {
+        let input = "Game 1: 3 blue\nGame 2: 4 bleu, 2 red\n";
+        let text = &input[25..29];
+        assert_eq!(text, "bleu");
+
+        let error = Error::at(input, text, "Unknown colour").in_day(2);
+        assert_eq!(error.line, Some(2));
+        assert_eq!(error.column, Some(11));
+        assert_eq!(
+            error.to_string(),
+            "day 02, line 2, column 11: Unknown colour: \"bleu\""
+        );
+    }
+
+    #[test]
+    fn test_error_outside_input() {
+        let error = Error::at("12 34", "56", "Unexpected number");
+        assert_eq!(error.line, None);
+        assert_eq!(error.column, None);
+        assert_eq!(error.to_string(), "Unexpected number: \"56\"");
+    }
+}
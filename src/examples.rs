@@ -0,0 +1,87 @@
+//! Regression tests running every day on its example inputs.
+//!
+//! Each example `input/NN_test*.txt` may have a matching `.answers` file with
+//! lines like `Part One: 142`. Parts without a line are not checked.
+
+use crate::DAYS;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn examples(day: u8) -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("input");
+    let prefix = format!("{:02}_test", day);
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".txt")
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn check_examples(day: u8) {
+    let (_, solution) = DAYS.iter().find(|(d, _)| *d == day).unwrap();
+    let mut checked = 0;
+
+    for path in examples(day) {
+        let Ok(answers) = fs::read_to_string(path.with_extension("answers")) else {
+            continue;
+        };
+        let input = fs::read_to_string(&path).unwrap();
+
+        for line in answers.lines().filter(|l| !l.trim().is_empty()) {
+            let (part, expected) = line.split_once(": ").unwrap();
+            let run = match part {
+                "Part One" => solution.run(1, &input),
+                "Part Two" => solution.run(2, &input),
+                _ => panic!("Unknown part in {}: {}", path.display(), part),
+            };
+            let answer = run
+                .unwrap_or_else(|err| panic!("{}: {}", path.display(), err))
+                .answer;
+            assert_eq!(
+                answer.to_string(),
+                expected.trim(),
+                "{} - {}",
+                path.display(),
+                part
+            );
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0, "No example answers for day {:02}", day);
+}
+
+macro_rules! example_tests {
+    ($($name:ident: $day:expr),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                check_examples($day);
+            }
+        )*
+    };
+}
+
+example_tests! {
+    day01: 1,
+    day02: 2,
+    day03: 3,
+    day04: 4,
+    day05: 5,
+    day06: 6,
+    day07: 7,
+    day08: 8,
+    day09: 9,
+}
+
+#[test]
+fn test_every_day_has_examples() {
+    for (day, _) in DAYS {
+        assert!(!examples(*day).is_empty(), "No examples for day {:02}", day);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::error::Result;
+use crate::parser::Parser;
+use std::ops::{Index, IndexMut};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Coord {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Coord {
+    pub fn new(row: usize, col: usize) -> Self {
+        Coord { row, col }
+    }
+
+    fn offset(&self, (row, col): (isize, isize)) -> Option<Coord> {
+        Some(Coord {
+            row: self.row.checked_add_signed(row)?,
+            col: self.col.checked_add_signed(col)?,
+        })
+    }
+}
+
+#[cfg(test)]
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid of cells stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// A grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+}
+
+impl Grid<char> {
+    /// Parse a grid with one character per cell and one line per row.
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_with(input, Some)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parse a grid converting each character, which is rejected when `cell`
+    /// returns `None`. Every line must have the same number of characters, and
+    /// trailing blank lines are ignored.
+    pub fn parse_with<F>(input: &str, mut cell: F) -> Result<Self>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let parser = Parser::new(input);
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+
+        for line in input.trim_end_matches(['\n', '\r']).lines() {
+            let line_width = line.chars().count();
+            let width = *width.get_or_insert(line_width);
+            if line_width != width {
+                let message = format!("Expected a line of width {}", width);
+                return Err(parser.error(line, message));
+            }
+
+            for (index, c) in line.char_indices() {
+                let text = &line[index..index + c.len_utf8()];
+                cells.push(cell(c).ok_or_else(|| parser.error(text, "Unexpected character"))?);
+            }
+            height += 1;
+        }
+
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.row < self.height && coord.col < self.width
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        if self.contains(coord) {
+            self.cells.get(coord.row * self.width + coord.col)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        if self.contains(coord) {
+            self.cells.get_mut(coord.row * self.width + coord.col)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(test)]
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        if row < self.height {
+            Some(&self.cells[row * self.width..(row + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    #[cfg(test)]
+    pub fn column(&self, col: usize) -> Option<impl Iterator<Item = &T>> {
+        if col < self.width {
+            Some(self.cells[col..].iter().step_by(self.width))
+        } else {
+            None
+        }
+    }
+
+    /// The cells above, below, left and right of `coord` within the grid.
+    #[cfg(test)]
+    pub fn neighbours4(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.neighbours(coord, &ORTHOGONAL)
+    }
+
+    /// The cells surrounding `coord` within the grid, diagonals included.
+    pub fn neighbours8(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.neighbours(coord, &SURROUNDING)
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        coord: Coord,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Coord> + 'a {
+        offsets
+            .iter()
+            .filter_map(move |&offset| coord.offset(offset))
+            .filter(|&c| self.contains(c))
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &T {
+        self.get(coord)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", coord))
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut T {
+        self.get_mut(coord)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", coord))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse("ab\ncd\nef\n\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(grid.get(Coord::new(2, 1)), Some(&'f'));
+        assert_eq!(grid.get(Coord::new(3, 0)), None);
+        assert_eq!(grid.get(Coord::new(0, 2)), None);
+        assert_eq!(grid.row(1), Some(&['c', 'd'][..]));
+        assert_eq!(grid.row(3), None);
+        assert_eq!(grid.rows().count(), 3);
+        let column: Vec<_> = grid.column(1).unwrap().collect();
+        assert_eq!(column, [&'b', &'d', &'f']);
+        assert!(grid.column(2).is_none());
+        assert_eq!(grid[Coord::new(0, 1)], 'b');
+
+        let mut grid = Grid::new(3, 2, 0);
+        grid[Coord::new(1, 2)] = 5;
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[0, 0, 0], [0, 0, 5]]);
+        assert!(grid.get_mut(Coord::new(2, 0)).is_none());
+
+        let grid = Grid::parse("é.\n.ü\n").unwrap();
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.get(Coord::new(1, 1)), Some(&'ü'));
+
+        let error = Grid::parse("...\n..\n").unwrap_err();
+        assert_eq!(error.message, "Expected a line of width 3");
+        assert_eq!(error.line, Some(2));
+
+        let grid = Grid::parse("").unwrap();
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let grid = Grid::parse_with("12\n34\n", |c| c.to_digit(10)).unwrap();
+        assert_eq!(grid.get(Coord::new(1, 0)), Some(&3));
+
+        let error = Grid::parse_with("12\n3x\n", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 2: Unexpected character: \"x\""
+        );
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::parse("...\n...\n...\n").unwrap();
+        let corner: Vec<_> = grid.neighbours4(Coord::new(0, 0)).collect();
+        assert_eq!(corner, [Coord::new(0, 1), Coord::new(1, 0)]);
+        assert_eq!(grid.neighbours8(Coord::new(0, 0)).count(), 3);
+        assert_eq!(grid.neighbours8(Coord::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbours4(Coord::new(1, 2)).count(), 3);
+        assert_eq!(grid.neighbours8(Coord::new(2, 1)).count(), 5);
+    }
+}
@@ -0,0 +1,65 @@
+use std::env;
+use std::fmt;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Environment variable overriding the directory puzzle inputs are read from.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+const DEFAULT_INPUT_DIR: &str = "input";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The puzzle input, `NN.txt` in the input directory.
+    Puzzle,
+    /// An example input, `NN_test.txt` or `NN_testK.txt` for the Kth example.
+    Example(Option<u8>),
+    File(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    /// Whether the source is only meaningful for a single day.
+    pub fn is_single(&self) -> bool {
+        matches!(self, Source::File(_) | Source::Stdin)
+    }
+
+    pub fn path(&self, day: u8) -> Option<PathBuf> {
+        match self {
+            Source::Puzzle => Some(input_dir().join(format!("{:02}.txt", day))),
+            Source::Example(None) => Some(input_dir().join(format!("{:02}_test.txt", day))),
+            Source::Example(Some(n)) => Some(input_dir().join(format!("{:02}_test{}.txt", day, n))),
+            Source::File(path) => Some(path.clone()),
+            Source::Stdin => None,
+        }
+    }
+
+    pub fn read(&self, day: u8) -> io::Result<String> {
+        match self.path(day) {
+            Some(path) => std::fs::read_to_string(path),
+            None => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Puzzle => write!(f, "puzzle input"),
+            Source::Example(None) => write!(f, "example input"),
+            Source::Example(Some(n)) => write!(f, "example input {}", n),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+pub fn input_dir() -> PathBuf {
+    env::var_os(INPUT_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_INPUT_DIR))
+}
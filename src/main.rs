@@ -1,3 +1,4 @@
+mod cli;
 mod day01;
 mod day02;
 mod day03;
@@ -23,57 +24,260 @@ mod day09;
 // mod day23;
 // mod day24;
 // mod day25;
+mod error;
+#[cfg(test)]
+mod examples;
+mod grid;
+mod input;
+mod parser;
+mod solution;
+mod timing;
+
+use cli::{Command, Dot, Hands, Selection};
+use input::Source;
+use solution::Solver;
+use timing::{format_duration, Stats, Table};
+
+const DAYS: &[(u8, &dyn Solver)] = &[
+    (1, &day01::Day01),
+    (2, &day02::Day02),
+    (3, &day03::Day03),
+    (4, &day04::Day04),
+    (5, &day05::Day05),
+    (6, &day06::Day06),
+    (7, &day07::Day07),
+    (8, &day08::Day08),
+    (9, &day09::Day09),
+];
+
+fn part_name(part: u8) -> &'static str {
+    if part == 1 {
+        "One"
+    } else {
+        "Two"
+    }
+}
+
+// Call `f` with the input of every selected part, reporting days whose input
+// cannot be read. Returns false if any input was missing.
+fn for_each_part<F>(selection: &Selection, mut f: F) -> bool
+where
+    F: FnMut(u8, &dyn Solver, u8, &str),
+{
+    if !DAYS.iter().any(|(day, _)| selection.days.contains(day)) {
+        eprintln!("No solutions for the selected days");
+        return false;
+    }
+
+    let mut ok = true;
+    for (day, solver) in DAYS {
+        if !selection.days.contains(day) {
+            continue;
+        }
+        let input = match selection.input.read(*day) {
+            Ok(input) => input,
+            Err(err) => {
+                let source = match selection.input.path(*day) {
+                    Some(path) => path.display().to_string(),
+                    None => selection.input.to_string(),
+                };
+                eprintln!("{:02} - Could not read {}: {}", day, source, err);
+                ok = false;
+                continue;
+            }
+        };
+
+        for part in [1, 2] {
+            if !selection.includes(*day, part) {
+                continue;
+            }
+            f(*day, *solver, part, &input);
+        }
+    }
+
+    ok
+}
+
+fn run(selection: &Selection) -> bool {
+    let mut failed = false;
+    let mut table = Table::new(&["Part", "Parse", "Solve", "Total"]);
+
+    let ok = for_each_part(selection, |day, solver, part, input| {
+        match solver.run(part, input) {
+            Ok(run) => {
+                println!("{:02} - Part {}: {}", day, part_name(part), run.answer);
+                table.push(vec![
+                    format!("{:02} - {}", day, part_name(part)),
+                    format_duration(run.parse),
+                    format_duration(run.solve),
+                    format_duration(run.total()),
+                ]);
+            }
+            Err(err) => {
+                eprintln!("{:02} - Part {}: {}", day, part_name(part), err.in_day(day));
+                failed = true;
+            }
+        }
+    });
+
+    // The summary only adds to the answers when comparing several parts
+    if table.len() > 1 {
+        println!("\n{}", table.render());
+    }
+    ok && !failed
+}
+
+fn bench(selection: &Selection, runs: usize) -> bool {
+    let mut failed = false;
+    let mut table = Table::new(&["Part", "Min", "Median", "Mean", "Parse", "Solve"]);
+
+    let ok = for_each_part(selection, |day, solver, part, input| {
+        let name = format!("{:02} - {}", day, part_name(part));
+        let mut samples = vec![];
+        for _ in 0..runs {
+            match solver.run(part, input) {
+                Ok(run) => samples.push(run),
+                Err(err) => {
+                    eprintln!("{:02} - Part {}: {}", day, part_name(part), err.in_day(day));
+                    failed = true;
+                    return;
+                }
+            }
+        }
+
+        let total = samples.iter().map(|r| r.total()).collect::<Vec<_>>();
+        let parse = samples.iter().map(|r| r.parse).collect::<Vec<_>>();
+        let solve = samples.iter().map(|r| r.solve).collect::<Vec<_>>();
+        let (Some(total), Some(parse), Some(solve)) =
+            (Stats::new(&total), Stats::new(&parse), Stats::new(&solve))
+        else {
+            return;
+        };
+        println!(
+            "{} ({} runs): {}",
+            name,
+            runs,
+            format_duration(total.median)
+        );
+        table.push(vec![
+            name,
+            format_duration(total.min),
+            format_duration(total.median),
+            format_duration(total.mean),
+            format_duration(parse.median),
+            format_duration(solve.median),
+        ]);
+    });
+
+    if table.len() > 0 {
+        println!("\n{}", table.render());
+    }
+    ok && !failed
+}
+
+// Read the input of a single day, reporting it if it cannot be read.
+fn read_day(source: &Source, day: u8) -> Option<String> {
+    match source.read(day) {
+        Ok(input) => Some(input),
+        Err(err) => {
+            let source = match source.path(day) {
+                Some(path) => path.display().to_string(),
+                None => source.to_string(),
+            };
+            eprintln!("{:02} - Could not read {}: {}", day, source, err);
+            None
+        }
+    }
+}
+
+fn dot(options: &Dot) -> bool {
+    let Some(input) = read_day(&options.input, 8) else {
+        return false;
+    };
+
+    let alphabet = match &options.alphabet {
+        Some(symbols) => day08::Alphabet::new(symbols),
+        None => day08::Alphabet::left_right(),
+    };
+    let (start, end) = day08::ghosts();
+    let matcher = |pattern: &Option<String>, default| match pattern {
+        Some(pattern) => day08::Matcher::regex(pattern),
+        None => Ok(default),
+    };
+
+    let dot = matcher(&options.start, start).and_then(|start| {
+        let end = matcher(&options.end, end)?;
+        let maps = day08::parse_maps(&input, &alphabet)?;
+        Ok(day08::to_dot(&maps, &start, &end, options.paths))
+    });
+    match dot {
+        Ok(dot) => {
+            print!("{}", dot);
+            true
+        }
+        Err(err) => {
+            eprintln!("{}", err.in_day(8));
+            false
+        }
+    }
+}
+
+fn hands(options: &Hands) -> bool {
+    let Some(input) = read_day(&options.input, 7) else {
+        return false;
+    };
+
+    let mut rules = match options.poker {
+        true => day07::Rules::poker(),
+        false => day07::Rules::camel_cards(),
+    };
+    let winnings = options
+        .wild
+        .as_deref()
+        .map(day07::parse_ranks)
+        .transpose()
+        .and_then(|wild| {
+            if let Some(wild) = wild {
+                rules.wild = wild;
+            }
+            let hands = day07::parse_hands(&input, &rules)?;
+            Ok(day07::winnings(&hands))
+        });
+    match winnings {
+        Ok(winnings) => {
+            println!("{}", winnings);
+            true
+        }
+        Err(err) => {
+            eprintln!("{}", err.in_day(7));
+            false
+        }
+    }
+}
 
 fn main() {
-    println!("Advent of Code 2023");
-    day01::solve_part_one();
-    day01::solve_part_two();
-    day02::solve_part_one();
-    day02::solve_part_two();
-    day03::solve_part_one();
-    day03::solve_part_two();
-    day04::solve_part_one();
-    day04::solve_part_two();
-    day05::solve_part_one();
-    // day05::solve_part_two();
-    day06::solve_part_one();
-    day06::solve_part_two();
-    day07::solve_part_one();
-    day07::solve_part_two();
-    day08::solve_part_one();
-    day08::solve_part_two();
-    day09::solve_part_one();
-    day09::solve_part_two();
-    // day10::solve_part_one();
-    // day10::solve_part_two();
-    // day11::solve_part_one();
-    // day11::solve_part_two();
-    // day12::solve_part_one();
-    // day12::solve_part_two();
-    // day13::solve_part_one();
-    // day13::solve_part_two();
-    // day14::solve_part_one();
-    // day14::solve_part_two();
-    // day15::solve_part_one();
-    // day15::solve_part_two();
-    // day16::solve_part_one();
-    // day16::solve_part_two();
-    // day17::solve_part_one();
-    // day17::solve_part_two();
-    // day18::solve_part_one();
-    // day18::solve_part_two();
-    // day19::solve_part_one();
-    // day19::solve_part_two();
-    // day20::solve_part_one();
-    // day20::solve_part_two();
-    // day21::solve_part_one();
-    // day21::solve_part_two();
-    // day22::solve_part_one();
-    // day22::solve_part_one();
-    // day23::solve_part_one();
-    // day23::solve_part_one();
-    // day24::solve_part_two();
-    // day24::solve_part_two();
-    // day25::solve_part_two();
-    // day25::solve_part_two();
+    let ok = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(Command::Run(selection)) => {
+            println!("Advent of Code 2023");
+            run(&selection)
+        }
+        Ok(Command::Bench(selection, runs)) => {
+            println!("Advent of Code 2023");
+            bench(&selection, runs)
+        }
+        Ok(Command::Dot(options)) => dot(&options),
+        Ok(Command::Hands(options)) => hands(&options),
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            true
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if !ok {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,64 @@
+use crate::error::{Error, Result};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Parsing helpers that report failures with their position in the input.
+///
+/// Every `text` passed in is expected to be a slice of the input the parser
+/// was created with, so that errors can point at the offending line and
+/// column.
+#[derive(Clone, Copy)]
+pub struct Parser<'a> {
+    input: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Parser { input }
+    }
+
+    pub fn error(&self, text: &str, message: impl Into<String>) -> Error {
+        Error::at(self.input, text, message)
+    }
+
+    pub fn split_once<'b>(&self, text: &'b str, delimiter: &str) -> Result<(&'b str, &'b str)> {
+        text.split_once(delimiter)
+            .ok_or_else(|| self.error(text, format!("Expected {:?}", delimiter)))
+    }
+
+    pub fn strip_prefix<'b>(&self, text: &'b str, prefix: &str) -> Result<&'b str> {
+        text.strip_prefix(prefix)
+            .ok_or_else(|| self.error(text, format!("Expected {:?} at the start", prefix)))
+    }
+
+    pub fn strip_suffix<'b>(&self, text: &'b str, suffix: &str) -> Result<&'b str> {
+        text.strip_suffix(suffix)
+            .ok_or_else(|| self.error(text, format!("Expected {:?} at the end", suffix)))
+    }
+
+    pub fn parse<T>(&self, text: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        text.parse::<T>()
+            .map_err(|err| self.error(text, capitalize(&err.to_string())))
+    }
+
+    /// Parse whitespace-separated numbers.
+    pub fn numbers<T>(&self, text: &str) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        text.split_whitespace().map(|n| self.parse(n)).collect()
+    }
+}
+
+fn capitalize(message: &str) -> String {
+    let mut chars = message.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
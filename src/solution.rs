@@ -0,0 +1,105 @@
+use crate::error::Result;
+use std::borrow::Borrow;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The answer to one part of a puzzle, kept in its printed form so answers of
+/// different numeric types can be compared against each other and against
+/// known answers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Answer(String);
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer(value.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    num::BigInt,
+    num::BigUint
+);
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer(value.to_string())
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub trait Solution {
+    /// The parsed input, which the parts see as a `View`.
+    type Input<'a>: Borrow<Self::View<'a>>;
+    /// The parsed input as the parts take it, such as `str` for a `&str` or a
+    /// slice for a `Vec`. Otherwise the same type as `Input`.
+    type View<'a>: ?Sized;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>>;
+    fn part_one(&self, input: &Self::View<'_>) -> Result<Answer>;
+    fn part_two(&self, input: &Self::View<'_>) -> Result<Answer>;
+}
+
+/// The outcome of running one part, with the time spent in each phase.
+#[derive(Clone, Debug)]
+pub struct Run {
+    pub answer: Answer,
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+impl Run {
+    pub fn total(&self) -> Duration {
+        self.parse + self.solve
+    }
+}
+
+/// Object-safe counterpart of [`Solution`], used by the runner to dispatch
+/// to days with different parsed input types.
+pub trait Solver: Sync {
+    fn run(&self, part: u8, input: &str) -> Result<Run>;
+}
+
+impl<S: Solution + Sync> Solver for S {
+    fn run(&self, part: u8, input: &str) -> Result<Run> {
+        let start = Instant::now();
+        let parsed = self.parse(input)?;
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        let answer = if part == 1 {
+            self.part_one(parsed.borrow())?
+        } else {
+            self.part_two(parsed.borrow())?
+        };
+        let solve = start.elapsed();
+
+        Ok(Run {
+            answer,
+            parse,
+            solve,
+        })
+    }
+}
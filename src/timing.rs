@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    pub fn new(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        let mean = sorted.iter().sum::<Duration>() / sorted.len() as u32;
+
+        Some(Stats {
+            min: sorted[0],
+            median,
+            mean,
+        })
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.2?}", duration)
+}
+
+/// A plain text table with a header row and right-aligned columns after the
+/// first one.
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(header: &[&str]) -> Self {
+        Table {
+            header: header.iter().map(|h| h.to_string()).collect(),
+            rows: vec![],
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn render(&self) -> String {
+        let mut widths: Vec<usize> = self.header.iter().map(|h| h.len()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let format_row = |row: &[String]| {
+            row.iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(index, (cell, width))| {
+                    if index == 0 {
+                        format!("{:<width$}", cell, width = width)
+                    } else {
+                        format!("{:>width$}", cell, width = width)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+        };
+
+        let mut lines = vec![format_row(&self.header)];
+        lines.push(
+            widths
+                .iter()
+                .map(|w| "-".repeat(*w))
+                .collect::<Vec<_>>()
+                .join("  "),
+        );
+        lines.extend(self.rows.iter().map(|row| format_row(row)));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let ms = Duration::from_millis;
+        let stats = Stats::new(&[ms(5), ms(1), ms(3), ms(7)]).unwrap();
+        assert_eq!(stats.min, ms(1));
+        assert_eq!(stats.median, ms(4));
+        assert_eq!(stats.mean, ms(4));
+
+        let stats = Stats::new(&[ms(2), ms(9), ms(1)]).unwrap();
+        assert_eq!(stats.median, ms(2));
+        assert_eq!(stats.mean, ms(4));
+
+        assert_eq!(Stats::new(&[]), None);
+    }
+
+    #[test]
+    fn test_table() {
+        let mut table = Table::new(&["Part", "Time"]);
+        table.push(vec![String::from("01 - One"), String::from("1.00ms")]);
+        table.push(vec![String::from("01 - Two"), String::from("12.50ms")]);
+        assert_eq!(
+            table.render(),
+            "Part         Time\n\
+             --------  -------\n\
+             01 - One   1.00ms\n\
+             01 - Two  12.50ms"
+        );
+    }
+}